@@ -0,0 +1,282 @@
+use crate::transform::{
+    apply, is_array_merge_call, is_attributed_call, is_jdt_syntax, JdtError, ATTR_ARRAY_MERGE,
+    ATTR_PATH, VERB_REMOVE, VERB_RENAME, VERB_REPLACE,
+};
+use serde_json::{Map, Value};
+
+/// Combine two transforms into one with the same effect as applying `first` and then
+/// `second` with default [`ApplyOptions`](crate::ApplyOptions).
+///
+/// The result is equivalent for every source. Constructs whose combined effect depends
+/// on the source, such as selector-based verbs followed by further changes or verbs
+/// under a key the source may not have, are all reported in
+/// [`JdtError::NotComposable`].
+pub fn compose(first: &Value, second: &Value) -> Result<Value, JdtError> {
+    let (Some(t1), Some(t2)) = (first.as_object(), second.as_object()) else {
+        return Err(JdtError::TransformNotObject);
+    };
+    let mut issues = Vec::new();
+    let composed = compose_node(t1, t2, "$", &mut issues);
+    if issues.is_empty() {
+        Ok(Value::Object(composed))
+    } else {
+        Err(JdtError::NotComposable(issues))
+    }
+}
+
+/// The verbs of one transform node, split by whether their targets are known statically.
+#[derive(Default)]
+struct NodeVerbs<'a> {
+    removes: Vec<&'a str>,
+    renames: Vec<(&'a str, &'a str)>,
+    /// Verbs whose targets depend on the source: selectors, replace and merge.
+    dynamic: Vec<&'a str>,
+}
+
+impl NodeVerbs<'_> {
+    fn touches(&self, name: &str) -> bool {
+        self.removes.contains(&name) || self.renames.iter().any(|&(o, n)| o == name || n == name)
+    }
+}
+
+fn node_verbs(node: &Map<String, Value>) -> NodeVerbs<'_> {
+    let mut verbs = NodeVerbs::default();
+    for (key, value) in node.iter().filter(|(k, _)| is_jdt_syntax(k)) {
+        match key.as_str() {
+            VERB_REMOVE => match named_removes(value) {
+                Some(names) => verbs.removes.extend(names),
+                None => verbs.dynamic.push(VERB_REMOVE),
+            },
+            VERB_RENAME => match named_renames(value) {
+                Some(pairs) => verbs.renames.extend(pairs),
+                None => verbs.dynamic.push(VERB_RENAME),
+            },
+            other => verbs.dynamic.push(other),
+        }
+    }
+    verbs
+}
+
+fn named_removes(value: &Value) -> Option<Vec<&str>> {
+    match value {
+        Value::String(name) => Some(vec![name.as_str()]),
+        Value::Array(items) => items.iter().map(Value::as_str).collect(),
+        _ => None,
+    }
+}
+
+fn named_renames(value: &Value) -> Option<Vec<(&str, &str)>> {
+    match value {
+        Value::Object(mapping) if !mapping.keys().any(|k| is_jdt_syntax(k)) => mapping
+            .iter()
+            .map(|(old, new)| new.as_str().map(|new| (old.as_str(), new)))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .map(named_renames)
+            .collect::<Option<Vec<_>>>()
+            .map(|pairs| pairs.concat()),
+        _ => None,
+    }
+}
+
+/// The replacement object of a whole-node `{"@jdt.replace": {...}}`.
+fn literal_replace(node: &Map<String, Value>) -> Option<&Map<String, Value>> {
+    node.get(VERB_REPLACE)
+        .and_then(Value::as_object)
+        .filter(|obj| !is_attributed_call(obj))
+}
+
+/// Whether a replace verb value can replace the node itself and skip the rest of it.
+fn replace_may_halt(value: &Value) -> bool {
+    match value {
+        Value::Object(obj) if is_attributed_call(obj) => {
+            obj.get(ATTR_PATH).and_then(Value::as_str).map(str::trim) == Some("$")
+        }
+        Value::Array(items) => items.iter().any(replace_may_halt),
+        _ => true,
+    }
+}
+
+fn has_jdt_syntax(value: &Value) -> bool {
+    value.as_object().is_some_and(|obj| {
+        obj.iter()
+            .any(|(k, v)| is_jdt_syntax(k) || has_jdt_syntax(v))
+    })
+}
+
+fn compose_node(
+    t1: &Map<String, Value>,
+    t2: &Map<String, Value>,
+    path: &str,
+    issues: &mut Vec<String>,
+) -> Map<String, Value> {
+    if t1.is_empty() {
+        return t2.clone();
+    }
+    if t2.is_empty() {
+        return t1.clone();
+    }
+    if literal_replace(t2).is_some() {
+        return t2.clone();
+    }
+    if let Some(replacement) = literal_replace(t1) {
+        // The first transform leaves exactly `replacement`, so the second can run on it now.
+        let second = Value::Object(t2.clone());
+        return match apply(&Value::Object(replacement.clone()), &second) {
+            Ok(value) => Map::from_iter([(VERB_REPLACE.to_string(), value)]),
+            Err(e) => {
+                issues.push(format!(
+                    "{path}: second transform fails on the replacement: {e}"
+                ));
+                t1.clone()
+            }
+        };
+    }
+
+    let v1 = node_verbs(t1);
+    let v2 = node_verbs(t2);
+    for verb in &v2.dynamic {
+        issues.push(format!(
+            "{path}: {verb} in the second transform depends on the result of the first"
+        ));
+    }
+    if t1.get(VERB_REPLACE).is_some_and(replace_may_halt) {
+        issues.push(format!(
+            "{path}: {VERB_REPLACE} in the first transform may replace the node the second changes"
+        ));
+    }
+    // Named verbs of the second transform move ahead of the first's replace, merge and
+    // default keys, which must not produce the names they act on.
+    if !v1.dynamic.is_empty() && (!v2.removes.is_empty() || !v2.renames.is_empty()) {
+        issues.push(format!(
+            "{path}: named {VERB_REMOVE}/{VERB_RENAME} in the second transform after selector-based verbs in the first"
+        ));
+    }
+    for &(old, new) in &v1.renames {
+        if v2.removes.contains(&old) || v2.removes.contains(&new) {
+            issues.push(format!(
+                "{path}.{old}: removed by the second transform after being renamed by the first"
+            ));
+        }
+    }
+    for &(old, new) in &v2.renames {
+        for name in [old, new] {
+            if t1.contains_key(name) || v1.renames.iter().any(|&(o, n)| o == name || n == name) {
+                issues.push(format!(
+                    "{path}.{name}: renamed by the second transform after being changed by the first"
+                ));
+            }
+        }
+    }
+
+    let mut out = Map::new();
+    let mut removes: Vec<&str> = Vec::new();
+    for name in v1.removes.iter().chain(&v2.removes) {
+        if !removes.contains(name) {
+            removes.push(name);
+        }
+    }
+    for (key, value) in t1.iter().filter(|(k, _)| is_jdt_syntax(k)) {
+        let rebuilt = match key.as_str() {
+            VERB_REMOVE => !v1.dynamic.contains(&VERB_REMOVE),
+            VERB_RENAME => true,
+            _ => false,
+        };
+        if !rebuilt {
+            out.insert(key.clone(), value.clone());
+        }
+    }
+    if !removes.is_empty() {
+        let names = removes.iter().map(|&n| Value::from(n)).collect();
+        out.insert(VERB_REMOVE.to_string(), Value::Array(names));
+    }
+    match (t1.get(VERB_RENAME), t2.get(VERB_RENAME)) {
+        (Some(r1), Some(r2)) => {
+            let mut steps = Vec::new();
+            for r in [r1, r2] {
+                match r {
+                    Value::Array(items) => steps.extend(items.iter().cloned()),
+                    other => steps.push(other.clone()),
+                }
+            }
+            out.insert(VERB_RENAME.to_string(), Value::Array(steps));
+        }
+        (Some(r), None) | (None, Some(r)) => {
+            out.insert(VERB_RENAME.to_string(), r.clone());
+        }
+        (None, None) => {}
+    }
+
+    for (key, value) in t1.iter().filter(|(k, _)| !is_jdt_syntax(k)) {
+        if !v2.removes.contains(&key.as_str()) {
+            out.insert(key.clone(), value.clone());
+        }
+    }
+    for (key, b) in t2.iter().filter(|(k, _)| !is_jdt_syntax(k)) {
+        let key_path = format!("{path}.{key}");
+        let nested = |v: &Value| v.is_object() && !is_array_merge_call(v);
+        // Nested changes run before the node's verbs, so they must not be affected by
+        // them. Once combined, the first transform's changes run when the second's would,
+        // so its verbs must leave the key alone whichever side is nested.
+        let first_nested = t1.get(key).is_some_and(nested);
+        if (nested(b) && v2.touches(key))
+            || ((nested(b) || first_nested) && (!v1.dynamic.is_empty() || v1.touches(key)))
+        {
+            issues.push(format!(
+                "{key_path}: nested changes would run before verbs that may change this key"
+            ));
+            continue;
+        }
+        let combined = match t1.get(key) {
+            Some(a) if !v2.removes.contains(&key.as_str()) => combine(a, b, &key_path, issues),
+            _ => b.clone(),
+        };
+        out.insert(key.clone(), combined);
+    }
+    out
+}
+
+/// Combine default-transform values set for the same key by both transforms.
+fn combine(a: &Value, b: &Value, path: &str, issues: &mut Vec<String>) -> Value {
+    if is_array_merge_call(b) {
+        let replaces = b.get(ATTR_ARRAY_MERGE).and_then(Value::as_str) == Some("replace");
+        if !replaces {
+            issues.push(format!(
+                "{path}: array merge policy in the second transform applied to the first's result"
+            ));
+        }
+        return b.clone();
+    }
+    match (a, b) {
+        (_, Value::Array(_) | Value::Object(_)) if is_array_merge_call(a) => {
+            issues.push(format!(
+                "{path}: array merge policy in the first transform followed by further changes"
+            ));
+            b.clone()
+        }
+        (Value::Array(x), Value::Array(y)) => Value::Array(x.iter().chain(y).cloned().collect()),
+        (_, Value::Array(_)) => {
+            issues.push(format!(
+                "{path}: an array replaces a non-array value, but would be appended to a source array"
+            ));
+            b.clone()
+        }
+        (Value::Object(x), Value::Object(y)) if !has_jdt_syntax(a) && !has_jdt_syntax(b) => {
+            Value::Object(compose_node(x, y, path, issues))
+        }
+        (Value::Object(_), Value::Object(_)) => {
+            issues.push(format!(
+                "{path}: transform syntax under a key the source may not have"
+            ));
+            b.clone()
+        }
+        (_, Value::Object(_)) => {
+            issues.push(format!(
+                "{path}: an object replaces a non-object value, but would be merged into a source object"
+            ));
+            b.clone()
+        }
+        _ => b.clone(),
+    }
+}
@@ -0,0 +1,191 @@
+use crate::jsonpath::keys_equal_ignore_case;
+use crate::transform::{array_index, JdtError, ATTR_ARRAY_MERGE, ATTR_VALUE};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+const COLON: &str = ":";
+const ENV_SEPARATOR: &str = "__";
+
+/// How the sections of a .NET configuration key are joined.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyStyle {
+    /// `Logging:LogLevel:Default`, as seen by `IConfiguration`.
+    #[default]
+    Colon,
+    /// `Logging__LogLevel__Default`, as read from environment variables.
+    EnvVar,
+}
+
+impl KeyStyle {
+    fn separator(self) -> &'static str {
+        match self {
+            KeyStyle::Colon => COLON,
+            KeyStyle::EnvVar => ENV_SEPARATOR,
+        }
+    }
+}
+
+/// Flatten `value` into .NET configuration keys and string values, in document order.
+///
+/// Array elements get their index as a section. Nulls become empty strings and empty
+/// objects and arrays are left out. The value must be an object, and an empty name or one
+/// containing `:` (or `__` with [`KeyStyle::EnvVar`]) fails with [`JdtError::ConfigKey`].
+pub fn flatten_config(value: &Value, style: KeyStyle) -> Result<Vec<(String, String)>, JdtError> {
+    let Value::Object(obj) = value else {
+        return Err(JdtError::SourceNotObject);
+    };
+    let mut out = Vec::new();
+    for (name, value) in obj {
+        flatten_into(&mut out, section(None, name, style)?, value, style)?;
+    }
+    Ok(out)
+}
+
+fn flatten_into(
+    out: &mut Vec<(String, String)>,
+    key: String,
+    value: &Value,
+    style: KeyStyle,
+) -> Result<(), JdtError> {
+    match value {
+        Value::Object(obj) => {
+            for (name, value) in obj {
+                flatten_into(out, section(Some(&key), name, style)?, value, style)?;
+            }
+        }
+        Value::Array(items) => {
+            for (i, value) in items.iter().enumerate() {
+                flatten_into(
+                    out,
+                    section(Some(&key), &i.to_string(), style)?,
+                    value,
+                    style,
+                )?;
+            }
+        }
+        Value::Null => out.push((key, String::new())),
+        Value::String(s) => out.push((key, s.clone())),
+        other => out.push((key, other.to_string())),
+    }
+    Ok(())
+}
+
+fn section(parent: Option<&str>, name: &str, style: KeyStyle) -> Result<String, JdtError> {
+    let separator = style.separator();
+    let key = match parent {
+        Some(parent) => format!("{parent}{separator}{name}"),
+        None => name.to_string(),
+    };
+    if name.is_empty() {
+        return Err(JdtError::ConfigKey {
+            key,
+            reason: "a section name is empty".to_string(),
+        });
+    }
+    if name.contains(COLON) || name.contains(separator) {
+        return Err(JdtError::ConfigKey {
+            key,
+            reason: format!("the name {name:?} contains a section separator"),
+        });
+    }
+    Ok(key)
+}
+
+/// Build a transform from .NET configuration keys and values, such as environment
+/// variables.
+///
+/// Keys are split on `:`, and also on `__` with [`KeyStyle::EnvVar`]. Values are kept
+/// as strings. A section whose children are all indices becomes an array merged by
+/// position, so `Hosts__1` overrides the second host and leaves the others; when the
+/// indices have gaps, its `@jdt.value` is an object keyed by index. Where the source holds
+/// an object or nothing rather than an array, the indices are merged as names, so none of
+/// them shift. Section names are compared ignoring case, as .NET does, keeping the first
+/// spelling. An empty section, as
+/// in `Hosts__` or `A::B`, a key set twice, or a key that is both a value and a section
+/// fail with [`JdtError::ConfigKey`].
+pub fn unflatten_config<I, K, V>(entries: I, style: KeyStyle) -> Result<Value, JdtError>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: Into<String>,
+{
+    let mut root = Tree::default();
+    for (key, value) in entries {
+        let key = key.as_ref();
+        let normalized = match style {
+            KeyStyle::Colon => key.to_string(),
+            KeyStyle::EnvVar => key.replace(ENV_SEPARATOR, COLON),
+        };
+        let conflict = |reason: &str| JdtError::ConfigKey {
+            key: key.to_string(),
+            reason: reason.to_string(),
+        };
+        let mut node = &mut root;
+        for name in normalized.split(COLON) {
+            if name.is_empty() {
+                return Err(conflict("a section name is empty"));
+            }
+            if node.value.is_some() {
+                return Err(conflict("a parent section already has a value"));
+            }
+            // .NET reads section names ignoring case; the first spelling is kept.
+            let name = node
+                .children
+                .keys()
+                .find(|k| keys_equal_ignore_case(k, name))
+                .cloned()
+                .unwrap_or_else(|| name.to_string());
+            node = node.children.entry(name).or_default();
+        }
+        if node.value.is_some() {
+            return Err(conflict("the key is set more than once"));
+        }
+        if !node.children.is_empty() {
+            return Err(conflict("the key is also a section"));
+        }
+        node.value = Some(value.into());
+    }
+    Ok(root.into_object())
+}
+
+#[derive(Default)]
+struct Tree {
+    value: Option<String>,
+    children: BTreeMap<String, Tree>,
+}
+
+impl Tree {
+    fn into_object(self) -> Value {
+        Value::Object(
+            self.children
+                .into_iter()
+                .map(|(name, child)| (name, child.into_value()))
+                .collect(),
+        )
+    }
+
+    fn into_value(self) -> Value {
+        if let Some(value) = self.value {
+            return Value::String(value);
+        }
+        if !self.children.keys().all(|k| array_index(k).is_some()) {
+            return self.into_object();
+        }
+        let mut children: Vec<_> = self.children.into_iter().collect();
+        children.sort_unstable_by_key(|(name, _)| array_index(name));
+        let dense = children
+            .iter()
+            .enumerate()
+            .all(|(i, (name, _))| array_index(name) == Some(i));
+        let items = children
+            .into_iter()
+            .map(|(name, child)| (name, child.into_value()));
+        // Listed indices are merged by position, leaving the others as they are.
+        let items = if dense {
+            Value::Array(items.map(|(_, v)| v).collect())
+        } else {
+            Value::Object(items.collect())
+        };
+        json!({ ATTR_ARRAY_MERGE: "positional", ATTR_VALUE: items })
+    }
+}
@@ -0,0 +1,101 @@
+use crate::jsonpath::PathItem;
+use crate::patch;
+use crate::transform::{
+    is_jdt_syntax, JdtError, ATTR_ARRAY_MERGE, ATTR_VALUE, VERB_REMOVE, VERB_REPLACE,
+};
+use serde_json::{Map, Value};
+
+/// Build a transform that turns `before` into `after` under default
+/// [`ApplyOptions`](crate::ApplyOptions), so that `apply(before, t) == after`.
+///
+/// Only changed keys appear in the transform. Added and changed values use the default
+/// transformation (arrays that gain elements at the end get just those elements),
+/// deleted keys a named `@jdt.remove`, an object that becomes another type a node-level
+/// `@jdt.replace`, and any other changed array a `"replace"` array merge. A transform
+/// would read keys that start with `@jdt.` as syntax, so a document holding one fails
+/// with [`JdtError::ReservedKey`].
+pub fn diff_to_transform(before: &Value, after: &Value) -> Result<Value, JdtError> {
+    let (Some(before_obj), Some(after_obj)) = (before.as_object(), after.as_object()) else {
+        return Err(JdtError::SourceNotObject);
+    };
+    check_keys(before, &mut Vec::new())?;
+    check_keys(after, &mut Vec::new())?;
+    Ok(Value::Object(diff_objects(before_obj, after_obj)))
+}
+
+/// Fails on the first key in `value` that a transform would read as JDT syntax.
+pub(crate) fn check_keys(value: &Value, path: &mut Vec<PathItem>) -> Result<(), JdtError> {
+    match value {
+        Value::Object(obj) => {
+            for (key, value) in obj {
+                path.push(PathItem::Key(key.clone()));
+                if is_jdt_syntax(key) {
+                    return Err(JdtError::ReservedKey(patch::pointer(path)));
+                }
+                check_keys(value, path)?;
+                path.pop();
+            }
+        }
+        Value::Array(items) => {
+            for (i, value) in items.iter().enumerate() {
+                path.push(PathItem::Index(i));
+                check_keys(value, path)?;
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn diff_objects(before: &Map<String, Value>, after: &Map<String, Value>) -> Map<String, Value> {
+    let mut transform = Map::new();
+    let removed: Vec<Value> = before
+        .keys()
+        .filter(|k| !after.contains_key(*k))
+        .map(|k| Value::String(k.clone()))
+        .collect();
+    match removed.len() {
+        0 => {}
+        1 => {
+            transform.insert(VERB_REMOVE.to_string(), removed[0].clone());
+        }
+        _ => {
+            transform.insert(VERB_REMOVE.to_string(), Value::Array(removed));
+        }
+    }
+
+    for (key, new) in after {
+        let change = match before.get(key) {
+            None => Some(new.clone()),
+            Some(old) if old == new => None,
+            Some(old) => Some(diff_values(old, new)),
+        };
+        if let Some(change) = change {
+            transform.insert(key.clone(), change);
+        }
+    }
+    transform
+}
+
+fn diff_values(old: &Value, new: &Value) -> Value {
+    match (old, new) {
+        (Value::Object(o), Value::Object(n)) => Value::Object(diff_objects(o, n)),
+        // A node-level replace with an array value is a list of replacements.
+        (Value::Object(_), Value::Array(_)) => Value::Object(Map::from_iter([(
+            VERB_REPLACE.to_string(),
+            Value::Array(vec![new.clone()]),
+        )])),
+        (Value::Object(_), _) => {
+            Value::Object(Map::from_iter([(VERB_REPLACE.to_string(), new.clone())]))
+        }
+        (Value::Array(o), Value::Array(n)) if n.starts_with(o) => {
+            Value::Array(n[o.len()..].to_vec())
+        }
+        (Value::Array(_), Value::Array(_)) => Value::Object(Map::from_iter([
+            (ATTR_ARRAY_MERGE.to_string(), Value::from("replace")),
+            (ATTR_VALUE.to_string(), new.clone()),
+        ])),
+        _ => new.clone(),
+    }
+}
@@ -1,4 +1,4 @@
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::fmt;
 use thiserror::Error;
 
@@ -131,6 +131,18 @@ impl JsonPath {
                         }
                         idx += 1;
                         segments.push(Segment::Filter(expr));
+                    } else if matches!(s.as_bytes()[idx], b'\'' | b'"') {
+                        // Quoted name: ['a.b'] or ["a.b"]
+                        let (name, next) = parse_quoted_name(s, idx)?;
+                        idx = next;
+                        if s.as_bytes().get(idx) != Some(&b']') {
+                            return Err(JsonPathError::Invalid {
+                                at: idx,
+                                msg: "expected ]",
+                            });
+                        }
+                        idx += 1;
+                        segments.push(Segment::Child(name));
                     } else {
                         let (seg, next) = parse_index_or_union(s, idx)?;
                         idx = next;
@@ -160,6 +172,16 @@ impl JsonPath {
     }
 
     pub fn select_paths(&self, root: &Value) -> Vec<Vec<PathItem>> {
+        self.select(root, false)
+    }
+
+    /// Like [`select_paths`](Self::select_paths), matching names in the path and its
+    /// filters case-insensitively; see [`find_key`] for which key is chosen.
+    pub fn select_paths_ignore_case(&self, root: &Value) -> Vec<Vec<PathItem>> {
+        self.select(root, true)
+    }
+
+    fn select(&self, root: &Value, ignore_case: bool) -> Vec<Vec<PathItem>> {
         let mut current: Vec<Vec<PathItem>> = vec![Vec::new()];
 
         for seg in &self.segments {
@@ -170,12 +192,13 @@ impl JsonPath {
                 };
                 match seg {
                     Segment::Child(name) => {
-                        if let Some(obj) = node.as_object() {
-                            if obj.contains_key(name) {
-                                let mut p = path.clone();
-                                p.push(PathItem::Key(name.clone()));
-                                next.push(p);
-                            }
+                        if let Some(key) = node
+                            .as_object()
+                            .and_then(|obj| find_key(obj, name, ignore_case))
+                        {
+                            let mut p = path.clone();
+                            p.push(PathItem::Key(key.clone()));
+                            next.push(p);
                         }
                     }
                     Segment::Index(index) => {
@@ -201,7 +224,7 @@ impl JsonPath {
                     Segment::Filter(expr) => match node {
                         Value::Array(arr) => {
                             for (i, el) in arr.iter().enumerate() {
-                                if filter_matches(expr, el) {
+                                if filter_matches(expr, el, ignore_case) {
                                     let mut p = path.clone();
                                     p.push(PathItem::Index(i));
                                     next.push(p);
@@ -210,7 +233,7 @@ impl JsonPath {
                         }
                         Value::Object(obj) => {
                             for (k, v) in obj.iter() {
-                                if filter_matches(expr, v) {
+                                if filter_matches(expr, v, ignore_case) {
                                     let mut p = path.clone();
                                     p.push(PathItem::Key(k.clone()));
                                     next.push(p);
@@ -253,6 +276,57 @@ fn parse_name(s: &str, at: usize) -> Result<String, JsonPathError> {
     Ok(s[at..end].to_string())
 }
 
+/// Parse a name quoted with `'` or `"` at `at`, with JSON escapes; only the enclosing
+/// quote can be escaped.
+fn parse_quoted_name(s: &str, at: usize) -> Result<(String, usize), JsonPathError> {
+    let quote = s.as_bytes()[at] as char;
+    let invalid = |msg| JsonPathError::Invalid { at, msg };
+    let mut out = String::new();
+    let mut chars = s[at + 1..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c == quote => return Ok((out, at + 1 + i + 1)),
+            '\\' => match chars.next().map(|(_, e)| e) {
+                Some('b') => out.push('\u{8}'),
+                Some('f') => out.push('\u{c}'),
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('t') => out.push('\t'),
+                Some(e @ ('/' | '\\')) => out.push(e),
+                Some(e) if e == quote => out.push(e),
+                Some('u') => {
+                    let mut code = hex_unit(&mut chars).ok_or(invalid("invalid \\u escape"))?;
+                    if (0xD800..0xDC00).contains(&code) {
+                        let low = match (chars.next(), chars.next()) {
+                            (Some((_, '\\')), Some((_, 'u'))) => hex_unit(&mut chars),
+                            _ => None,
+                        };
+                        let low = low
+                            .filter(|low| (0xDC00..0xE000).contains(low))
+                            .ok_or(invalid("unpaired surrogate"))?;
+                        code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                    }
+                    out.push(char::from_u32(code).ok_or(invalid("unpaired surrogate"))?);
+                }
+                _ => return Err(invalid("invalid escape")),
+            },
+            '\0'..='\u{1f}' => return Err(invalid("unescaped control character")),
+            c => out.push(c),
+        }
+    }
+    Err(invalid("unterminated name"))
+}
+
+/// The UTF-16 code unit of the four hex digits after `\u`.
+fn hex_unit(chars: &mut std::str::CharIndices) -> Option<u32> {
+    let hex: String = chars.take(4).map(|(_, h)| h).collect();
+    if hex.len() == 4 && hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        u32::from_str_radix(&hex, 16).ok()
+    } else {
+        None
+    }
+}
+
 fn parse_index_or_union(s: &str, mut at: usize) -> Result<(Segment, usize), JsonPathError> {
     let mut indices = Vec::<i64>::new();
     loop {
@@ -406,17 +480,43 @@ fn normalize_index(index: i64, len: usize) -> Option<usize> {
     }
 }
 
-fn filter_matches(expr: &FilterExpr, candidate: &Value) -> bool {
+fn filter_matches(expr: &FilterExpr, candidate: &Value, ignore_case: bool) -> bool {
+    let field = |name: &str| {
+        let obj = candidate.as_object()?;
+        obj.get(find_key(obj, name, ignore_case)?)
+    };
     match expr {
-        FilterExpr::Exists(name) => match candidate.as_object() {
-            Some(obj) => obj.get(name).is_some_and(|v| !v.is_null()),
-            None => false,
-        },
-        FilterExpr::Equals(name, lit) => match candidate.as_object() {
-            Some(obj) => obj.get(name).is_some_and(|v| v == lit),
-            None => false,
-        },
+        FilterExpr::Exists(name) => field(name).is_some_and(|v| !v.is_null()),
+        FilterExpr::Equals(name, lit) => field(name).is_some_and(|v| v == lit),
+    }
+}
+
+/// The key of `obj` that `name` refers to. Ignoring case, an exact match wins over
+/// the first key equal to `name` in another case, like .NET's `OrdinalIgnoreCase`.
+pub(crate) fn find_key<'a>(
+    obj: &'a Map<String, Value>,
+    name: &str,
+    ignore_case: bool,
+) -> Option<&'a String> {
+    if let Some((key, _)) = obj.get_key_value(name) {
+        return Some(key);
+    }
+    if !ignore_case {
+        return None;
+    }
+    obj.keys().find(|key| keys_equal_ignore_case(key, name))
+}
+
+/// Compares one character at a time, so `ß` does not match `SS`.
+pub(crate) fn keys_equal_ignore_case(a: &str, b: &str) -> bool {
+    fn upper(c: char) -> char {
+        let mut upper = c.to_uppercase();
+        match (upper.next(), upper.next()) {
+            (Some(u), None) => u,
+            _ => c,
+        }
     }
+    a.chars().map(upper).eq(b.chars().map(upper))
 }
 
 fn get_at<'a>(root: &'a Value, path: &[PathItem]) -> Option<&'a Value> {
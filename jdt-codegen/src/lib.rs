@@ -1,8 +1,42 @@
+mod compose;
+mod config;
+mod diff;
 mod jsonpath;
+mod order;
+mod params;
+mod patch;
+mod provider;
+mod text;
+#[cfg(feature = "toml")]
+mod toml;
+mod trace;
 mod transform;
+#[cfg(feature = "yaml")]
+mod yaml;
 
+pub use crate::compose::compose;
+pub use crate::config::{flatten_config, unflatten_config, KeyStyle};
+pub use crate::diff::diff_to_transform;
 pub use crate::jsonpath::{JsonPath, JsonPathError, PathItem};
-pub use crate::transform::{apply, JdtError};
+pub use crate::params::{apply_with, resolve_params, Params};
+pub use crate::patch::{apply_json_patch, json_patch_to_transform, merge_patch_to_transform};
+pub use crate::provider::{
+    apply_with_provider, resolve_refs, EnvProvider, MemoryProvider, ValueProvider,
+};
+pub use crate::text::{parse_json5, parse_jsonc, InputFormat};
+#[cfg(feature = "toml")]
+pub use crate::toml::{parse_toml, to_toml};
+pub use crate::trace::TraceEvent;
+#[cfg(feature = "toml")]
+pub use crate::transform::apply_toml;
+#[cfg(feature = "yaml")]
+pub use crate::transform::apply_yaml;
+pub use crate::transform::{
+    apply, apply_all, apply_in_place, apply_text, apply_traced, apply_with_inverse,
+    apply_with_patch, apply_with_unused, ApplyOptions, ArrayMerge, JdtError, RenameCollision,
+};
+#[cfg(feature = "yaml")]
+pub use crate::yaml::{parse_yaml, to_yaml};
 
 /// Strip a leading UTF-8 BOM (U+FEFF) from a string, if present.
 pub fn strip_bom(s: &str) -> &str {
@@ -0,0 +1,47 @@
+use serde_json::{Map, Value};
+
+/// Remove `key` from `obj`, returning its position and value. The other keys keep their
+/// order; without the `preserve_order` feature keys are sorted and the position is `0`.
+pub(crate) fn remove_key(obj: &mut Map<String, Value>, key: &str) -> Option<(usize, Value)> {
+    #[cfg(feature = "preserve_order")]
+    {
+        let position = obj.keys().position(|k| k == key)?;
+        obj.shift_remove(key).map(|value| (position, value))
+    }
+    #[cfg(not(feature = "preserve_order"))]
+    {
+        obj.remove(key).map(|value| (0, value))
+    }
+}
+
+/// Insert `key` at `position`, moving an existing `key` there.
+pub(crate) fn insert_key(
+    obj: &mut Map<String, Value>,
+    position: usize,
+    key: String,
+    value: Value,
+) -> Option<Value> {
+    #[cfg(feature = "preserve_order")]
+    {
+        obj.shift_insert(position, key, value)
+    }
+    #[cfg(not(feature = "preserve_order"))]
+    {
+        let _ = position;
+        obj.insert(key, value)
+    }
+}
+
+/// Rename `old` to `new` in place, returning the position and value of an existing
+/// `new` that was overwritten. `old` must be present and differ from `new`.
+pub(crate) fn rename_key(
+    obj: &mut Map<String, Value>,
+    old: &str,
+    new: String,
+) -> Option<(usize, Value)> {
+    let overwritten = remove_key(obj, &new);
+    if let Some((position, value)) = remove_key(obj, old) {
+        insert_key(obj, position, new, value);
+    }
+    overwritten
+}
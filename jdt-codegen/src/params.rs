@@ -0,0 +1,195 @@
+use crate::provider::{self, ValueProvider, ATTR_REF};
+use crate::transform::{apply, JdtError};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+const ATTR_PARAM: &str = "@jdt.param";
+const ATTR_TYPE: &str = "@jdt.type";
+
+/// Named values bound into a transform through [`ApplyOptions::params`](crate::ApplyOptions::params).
+///
+/// A transform references a parameter either as a whole value,
+/// `{ "@jdt.param": "dbHost" }` (optionally with `"@jdt.type"`), or inside a string as
+/// `${dbHost}`. Interpolated parameters must be strings, numbers or booleans; `$${`
+/// produces a literal `${`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params {
+    values: BTreeMap<String, Value>,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind `name` to `value`, returning the previous binding if any.
+    pub fn insert(&mut self, name: impl Into<String>, value: Value) -> Option<Value> {
+        self.values.insert(name.into(), value)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+}
+
+impl From<Map<String, Value>> for Params {
+    fn from(map: Map<String, Value>) -> Self {
+        Self {
+            values: map.into_iter().collect(),
+        }
+    }
+}
+
+impl<K: Into<String>> FromIterator<(K, Value)> for Params {
+    fn from_iter<I: IntoIterator<Item = (K, Value)>>(iter: I) -> Self {
+        Self {
+            values: iter.into_iter().map(|(k, v)| (k.into(), v)).collect(),
+        }
+    }
+}
+
+/// Apply `transform` to `source` after substituting every parameter reference.
+pub fn apply_with(source: &Value, transform: &Value, params: &Params) -> Result<Value, JdtError> {
+    apply(source, &resolve_params(transform, params)?)
+}
+
+/// Substitute every parameter reference in `transform`, failing if a referenced
+/// parameter is not supplied or does not have the expected type.
+pub fn resolve_params(transform: &Value, params: &Params) -> Result<Value, JdtError> {
+    resolve(transform, Some(params), None, false)
+}
+
+/// Substitute the parameter references in `transform` when `params` is given and the
+/// `@jdt.ref` references when `provider` is, in a single pass. With `complete`, a
+/// `@jdt.param` or `@jdt.ref` object that nothing binds fails; otherwise it is left as
+/// written. Strings are only interpolated when `params` is given.
+pub(crate) fn resolve(
+    transform: &Value,
+    params: Option<&Params>,
+    provider: Option<&dyn ValueProvider>,
+    complete: bool,
+) -> Result<Value, JdtError> {
+    match transform {
+        Value::Object(obj) => match (params, provider) {
+            (Some(params), _) if obj.contains_key(ATTR_PARAM) => resolve_reference(obj, params),
+            (None, _) if complete && obj.contains_key(ATTR_PARAM) => Err(unbound(obj)),
+            (_, Some(provider)) if obj.contains_key(ATTR_REF) => {
+                provider::resolve_reference(obj, provider)
+            }
+            (_, None) if complete && obj.contains_key(ATTR_REF) => Err(unbound(obj)),
+            _ => {
+                let mut out = Map::new();
+                for (k, v) in obj {
+                    out.insert(k.clone(), resolve(v, params, provider, complete)?);
+                }
+                Ok(Value::Object(out))
+            }
+        },
+        Value::Array(arr) => arr
+            .iter()
+            .map(|v| resolve(v, params, provider, complete))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        Value::String(s) => match params {
+            Some(params) => interpolate(s, params).map(Value::String),
+            None => Ok(transform.clone()),
+        },
+        other => Ok(other.clone()),
+    }
+}
+
+/// Fail on the first `@jdt.param` or `@jdt.ref` object in `transform`, which is applied
+/// without params or a provider.
+pub(crate) fn reject_references(transform: &Value) -> Result<(), JdtError> {
+    match transform {
+        Value::Object(obj) if obj.contains_key(ATTR_PARAM) || obj.contains_key(ATTR_REF) => {
+            Err(unbound(obj))
+        }
+        Value::Object(obj) => obj.values().try_for_each(reject_references),
+        Value::Array(arr) => arr.iter().try_for_each(reject_references),
+        _ => Ok(()),
+    }
+}
+
+/// The error for the reference object `obj` when nothing supplies its value.
+fn unbound(obj: &Map<String, Value>) -> JdtError {
+    let (attr, error): (_, fn(String) -> JdtError) = if obj.contains_key(ATTR_PARAM) {
+        (ATTR_PARAM, JdtError::MissingParam)
+    } else {
+        (ATTR_REF, JdtError::UnresolvedRef)
+    };
+    match obj.get(attr).and_then(Value::as_str) {
+        Some(name) => error(name.to_string()),
+        None => JdtError::AttributeNotString(attr),
+    }
+}
+
+fn resolve_reference(obj: &Map<String, Value>, params: &Params) -> Result<Value, JdtError> {
+    let name = obj
+        .get(ATTR_PARAM)
+        .and_then(Value::as_str)
+        .ok_or(JdtError::AttributeNotString(ATTR_PARAM))?;
+    let value = params
+        .get(name)
+        .ok_or_else(|| JdtError::MissingParam(name.to_string()))?;
+    if let Some(ty) = obj.get(ATTR_TYPE) {
+        let ty = ty.as_str().ok_or(JdtError::AttributeNotString(ATTR_TYPE))?;
+        let ok = match ty {
+            "string" => value.is_string(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "boolean" => value.is_boolean(),
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            other => {
+                return Err(JdtError::InvalidAttributeValue(
+                    ATTR_TYPE,
+                    other.to_string(),
+                ))
+            }
+        };
+        if !ok {
+            return Err(JdtError::ParamType {
+                name: name.to_string(),
+                expected: ty.to_string(),
+            });
+        }
+    }
+    Ok(value.clone())
+}
+
+fn interpolate(s: &str, params: &Params) -> Result<String, JdtError> {
+    if !s.contains("${") {
+        return Ok(s.to_string());
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            // `$${` escapes a literal `${`.
+            out.push_str(&rest[..start - 1]);
+            out.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let name = &rest[start + 2..start + 2 + len];
+        match params.get(name) {
+            Some(Value::String(v)) => out.push_str(v),
+            Some(v @ (Value::Number(_) | Value::Bool(_))) => out.push_str(&v.to_string()),
+            Some(_) => {
+                return Err(JdtError::ParamType {
+                    name: name.to_string(),
+                    expected: "string, number or boolean".to_string(),
+                })
+            }
+            None => return Err(JdtError::MissingParam(name.to_string())),
+        }
+        rest = &rest[start + 2 + len + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
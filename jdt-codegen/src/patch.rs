@@ -0,0 +1,519 @@
+use crate::diff;
+use crate::jsonpath::PathItem;
+use crate::order;
+use crate::transform::{
+    JdtError, ATTR_ARRAY_MERGE, ATTR_EXPECT, ATTR_PATH, ATTR_VALUE, VERB_MERGE, VERB_REMOVE,
+    VERB_RENAME, VERB_REPLACE,
+};
+use serde_json::{json, Map, Value};
+
+/// A mutation made while applying a transform, with paths from the document root.
+#[derive(Debug, Clone)]
+pub(crate) enum Edit {
+    Add {
+        path: Vec<PathItem>,
+        value: Value,
+    },
+    /// `position` is the removed entry's place among its siblings.
+    Remove {
+        path: Vec<PathItem>,
+        old: Value,
+        position: usize,
+    },
+    Replace {
+        path: Vec<PathItem>,
+        old: Value,
+        value: Value,
+    },
+    /// A rename; `overwritten` is the position and value previously at `path`, if any.
+    Move {
+        from: Vec<PathItem>,
+        path: Vec<PathItem>,
+        overwritten: Option<(usize, Value)>,
+    },
+}
+
+/// Render `edits` as an RFC 6902 JSON Patch.
+pub(crate) fn to_json_patch(edits: &[Edit]) -> Value {
+    let ops = edits
+        .iter()
+        .map(|edit| match edit {
+            Edit::Add { path, value } => {
+                json!({"op": "add", "path": pointer(path), "value": value})
+            }
+            Edit::Remove { path, .. } => json!({"op": "remove", "path": pointer(path)}),
+            Edit::Replace { path, value, .. } => {
+                json!({"op": "replace", "path": pointer(path), "value": value})
+            }
+            Edit::Move { from, path, .. } => {
+                json!({"op": "move", "from": pointer(from), "path": pointer(path)})
+            }
+        })
+        .collect();
+    Value::Array(ops)
+}
+
+/// The edits that undo `edits`, in the order they must run.
+pub(crate) fn invert(edits: &[Edit]) -> Vec<Edit> {
+    let mut out = Vec::with_capacity(edits.len());
+    for edit in edits.iter().rev() {
+        match edit {
+            Edit::Add { path, value } => out.push(Edit::Remove {
+                path: path.clone(),
+                old: value.clone(),
+                position: 0,
+            }),
+            Edit::Remove { path, old, .. } => out.push(Edit::Add {
+                path: path.clone(),
+                value: old.clone(),
+            }),
+            Edit::Replace { path, old, value } => out.push(Edit::Replace {
+                path: path.clone(),
+                old: value.clone(),
+                value: old.clone(),
+            }),
+            Edit::Move {
+                from,
+                path,
+                overwritten,
+            } => {
+                out.push(Edit::Move {
+                    from: path.clone(),
+                    path: from.clone(),
+                    overwritten: None,
+                });
+                if let Some((_, value)) = overwritten {
+                    out.push(Edit::Add {
+                        path: path.clone(),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Revert `edits`, the mutations made to `doc` so far, leaving it as it was before,
+/// key order included.
+pub(crate) fn undo(doc: &mut Value, edits: Vec<Edit>) {
+    for edit in edits.into_iter().rev() {
+        match edit {
+            Edit::Add { path, .. } => {
+                take(doc, &path);
+            }
+            Edit::Remove {
+                path,
+                old,
+                position,
+            } => put(doc, &path, position, old),
+            Edit::Replace { path, old, .. } => {
+                if let Some(value) = doc.pointer_mut(&pointer(&path)) {
+                    *value = old;
+                }
+            }
+            Edit::Move {
+                from,
+                path,
+                overwritten,
+            } => {
+                if let Some((position, moved)) = take(doc, &path) {
+                    put(doc, &from, position, moved);
+                }
+                if let Some((position, value)) = overwritten {
+                    put(doc, &path, position, value);
+                }
+            }
+        }
+    }
+}
+
+/// Remove the entry at `path`, returning its position among its siblings and its value.
+fn take(doc: &mut Value, path: &[PathItem]) -> Option<(usize, Value)> {
+    let (last, parent) = path.split_last()?;
+    match (doc.pointer_mut(&pointer(parent))?, last) {
+        (Value::Object(obj), PathItem::Key(k)) => order::remove_key(obj, k),
+        (Value::Array(arr), PathItem::Index(i)) if *i < arr.len() => Some((*i, arr.remove(*i))),
+        _ => None,
+    }
+}
+
+/// Insert `value` at `path`, placing an object key at `position`.
+fn put(doc: &mut Value, path: &[PathItem], position: usize, value: Value) {
+    let Some((last, parent)) = path.split_last() else {
+        *doc = value;
+        return;
+    };
+    match (doc.pointer_mut(&pointer(parent)), last) {
+        (Some(Value::Object(obj)), PathItem::Key(k)) => {
+            order::insert_key(obj, position, k.clone(), value);
+        }
+        (Some(Value::Array(arr)), PathItem::Index(i)) if *i <= arr.len() => arr.insert(*i, value),
+        _ => debug_assert!(false, "journal out of sync at {}", pointer(path)),
+    }
+}
+
+/// Apply an RFC 6902 JSON Patch to a copy of `document`.
+///
+/// All six operations are supported; a failing `test` or a path that does not exist
+/// fails with [`JdtError::InvalidPatch`].
+pub fn apply_json_patch(document: &Value, patch: &Value) -> Result<Value, JdtError> {
+    let ops = patch
+        .as_array()
+        .ok_or_else(|| JdtError::InvalidPatch("patch must be an array".to_string()))?;
+    let mut doc = document.clone();
+    for op in ops {
+        let field = |name: &str| {
+            op.get(name).and_then(Value::as_str).ok_or_else(|| {
+                JdtError::InvalidPatch(format!("operation needs a string \"{name}\""))
+            })
+        };
+        let value = || {
+            op.get("value")
+                .cloned()
+                .ok_or_else(|| JdtError::InvalidPatch("operation needs a \"value\"".to_string()))
+        };
+        let path = field("path")?;
+        match field("op")? {
+            "add" => pointer_add(&mut doc, path, value()?)?,
+            "remove" => {
+                pointer_remove(&mut doc, path)?;
+            }
+            "replace" => *pointer_get(&mut doc, path)? = value()?,
+            "move" => {
+                let moved = pointer_remove(&mut doc, field("from")?)?;
+                pointer_add(&mut doc, path, moved)?;
+            }
+            "copy" => {
+                let copied = pointer_get(&mut doc, field("from")?)?.clone();
+                pointer_add(&mut doc, path, copied)?;
+            }
+            "test" => {
+                if *pointer_get(&mut doc, path)? != value()? {
+                    return Err(JdtError::InvalidPatch(format!("test failed at {path}")));
+                }
+            }
+            other => return Err(JdtError::InvalidPatch(format!("unknown operation {other}"))),
+        }
+    }
+    Ok(doc)
+}
+
+fn pointer_get<'a>(doc: &'a mut Value, pointer: &str) -> Result<&'a mut Value, JdtError> {
+    parse_pointer(pointer)?;
+    doc.pointer_mut(pointer)
+        .ok_or_else(|| JdtError::InvalidPatch(format!("path not found: {pointer}")))
+}
+
+/// The container holding the last segment of `pointer`, and that segment.
+fn pointer_parent<'a>(
+    doc: &'a mut Value,
+    pointer: &str,
+) -> Result<(&'a mut Value, String), JdtError> {
+    let (parent, _) = pointer
+        .rsplit_once('/')
+        .ok_or_else(|| JdtError::InvalidPatch(format!("invalid JSON Pointer: {pointer}")))?;
+    let last = parse_pointer(pointer)?.pop().unwrap_or_default();
+    Ok((pointer_get(doc, parent)?, last))
+}
+
+fn array_index(last: &str, len: usize, pointer: &str) -> Result<usize, JdtError> {
+    match last.parse::<usize>() {
+        Ok(i) if is_index(last) && i < len => Ok(i),
+        _ => Err(JdtError::InvalidPatch(format!("path not found: {pointer}"))),
+    }
+}
+
+fn pointer_add(doc: &mut Value, pointer: &str, value: Value) -> Result<(), JdtError> {
+    if pointer.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, last) = pointer_parent(doc, pointer)?;
+    match parent {
+        Value::Object(obj) => {
+            obj.insert(last, value);
+        }
+        Value::Array(arr) if last == "-" => arr.push(value),
+        Value::Array(arr) => {
+            let i = array_index(&last, arr.len() + 1, pointer)?;
+            arr.insert(i, value);
+        }
+        _ => return Err(JdtError::InvalidPatch(format!("path not found: {pointer}"))),
+    }
+    Ok(())
+}
+
+fn pointer_remove(doc: &mut Value, pointer: &str) -> Result<Value, JdtError> {
+    let (parent, last) = pointer_parent(doc, pointer)?;
+    let removed = match parent {
+        Value::Object(obj) => order::remove_key(obj, &last).map(|(_, value)| value),
+        Value::Array(arr) => {
+            let i = array_index(&last, arr.len(), pointer)?;
+            Some(arr.remove(i))
+        }
+        _ => None,
+    };
+    removed.ok_or_else(|| JdtError::InvalidPatch(format!("path not found: {pointer}")))
+}
+
+/// Format `path` as an RFC 6901 JSON Pointer.
+pub(crate) fn pointer(path: &[PathItem]) -> String {
+    let mut out = String::new();
+    for item in path {
+        out.push('/');
+        match item {
+            PathItem::Key(k) => out.push_str(&k.replace('~', "~0").replace('/', "~1")),
+            PathItem::Index(i) => out.push_str(&i.to_string()),
+        }
+    }
+    out
+}
+
+/// Convert an RFC 6902 JSON Patch into an equivalent transform.
+///
+/// Operations run in order as the elements of a root `@jdt.merge`. Pointer segments
+/// made of digits address array elements. `add` into an array only supports appending
+/// with `-`, and `move` only renames within one object; these, `copy` and `test` fail
+/// with [`JdtError::UnsupportedPatchOp`].
+///
+/// Each selector carries a `@jdt.expect`, so a path or an `add` parent that does not
+/// exist fails with [`JdtError::UnexpectedMatchCount`], and the transform also runs in
+/// strict mode. The type of an `add` parent is not checked: adding `/a/x` where `a` is an
+/// array or a scalar replaces `a` with `{"x": ...}`, while [`apply_json_patch`] fails.
+/// Such patches are unsupported, as is replacing the whole document with a value that is
+/// not an object.
+pub fn json_patch_to_transform(patch: &Value) -> Result<Value, JdtError> {
+    let ops = patch
+        .as_array()
+        .ok_or_else(|| JdtError::InvalidPatch("patch must be an array".to_string()))?;
+    let steps = ops
+        .iter()
+        .map(patch_op_to_transform)
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    Ok(json!({ VERB_MERGE: steps }))
+}
+
+fn patch_op_to_transform(op: &Value) -> Result<Vec<Value>, JdtError> {
+    let field = |name: &str| {
+        op.get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| JdtError::InvalidPatch(format!("operation needs a string \"{name}\"")))
+    };
+    let value = || {
+        op.get("value")
+            .ok_or_else(|| JdtError::InvalidPatch("operation needs a \"value\"".to_string()))
+    };
+    let kind = field("op")?;
+    let path = parse_pointer(field("path")?)?;
+    if path.is_empty() && matches!(kind, "add" | "replace") && !value()?.is_object() {
+        return Err(JdtError::UnsupportedPatchOp(format!(
+            "{kind} of the whole document with a non-object value"
+        )));
+    }
+    let steps = match kind {
+        "remove" => {
+            if path.is_empty() {
+                return Err(JdtError::UnsupportedPatchOp(
+                    "remove of the whole document".to_string(),
+                ));
+            }
+            vec![json!({ VERB_REMOVE: { ATTR_PATH: selector(&path), ATTR_EXPECT: 1 } })]
+        }
+        "replace" => vec![json!({ VERB_REPLACE: {
+            ATTR_PATH: selector(&path),
+            ATTR_EXPECT: 1,
+            ATTR_VALUE: value()?,
+        } })],
+        "add" => match path.split_last() {
+            None => vec![json!({ VERB_REPLACE: { ATTR_PATH: "$", ATTR_VALUE: value()? } })],
+            Some((last, parent)) if last == "-" => vec![json!({ VERB_MERGE: {
+                ATTR_PATH: selector(parent),
+                ATTR_EXPECT: 1,
+                ATTR_ARRAY_MERGE: "append",
+                ATTR_VALUE: [value()?],
+            } })],
+            Some((last, _)) if is_index(last) => {
+                return Err(JdtError::UnsupportedPatchOp(format!(
+                    "add inserting into an array at {}",
+                    op["path"]
+                )))
+            }
+            Some((last, parent)) => {
+                // Remove first so the value is set rather than merged into an existing one.
+                let mut member = Map::new();
+                member.insert(last.clone(), value()?.clone());
+                vec![
+                    json!({ VERB_REMOVE: { ATTR_PATH: selector(&path), ATTR_EXPECT: "0..1" } }),
+                    json!({ VERB_MERGE: {
+                        ATTR_PATH: selector(parent),
+                        ATTR_EXPECT: 1,
+                        ATTR_VALUE: member,
+                    } }),
+                ]
+            }
+        },
+        "move" => {
+            let from = parse_pointer(field("from")?)?;
+            match (from.split_last(), path.split_last()) {
+                (Some((_, from_parent)), Some((name, parent)))
+                    if from_parent == parent && !is_index(name) =>
+                {
+                    vec![json!({ VERB_RENAME: {
+                        ATTR_PATH: selector(&from),
+                        ATTR_EXPECT: 1,
+                        ATTR_VALUE: name,
+                    } })]
+                }
+                _ => {
+                    return Err(JdtError::UnsupportedPatchOp(
+                        "move between different parents".to_string(),
+                    ))
+                }
+            }
+        }
+        other => return Err(JdtError::UnsupportedPatchOp(other.to_string())),
+    };
+    Ok(steps)
+}
+
+fn parse_pointer(pointer: &str) -> Result<Vec<String>, JdtError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(JdtError::InvalidPatch(format!(
+            "invalid JSON Pointer: {pointer}"
+        )));
+    };
+    Ok(rest
+        .split('/')
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+fn is_index(segment: &str) -> bool {
+    segment == "0"
+        || (!segment.is_empty()
+            && !segment.starts_with('0')
+            && segment.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// A JSONPath from the root for pointer `segments`, naming keys in quoted brackets so
+/// that any name can be addressed.
+fn selector(segments: &[String]) -> String {
+    let mut out = String::from("$");
+    for segment in segments {
+        if is_index(segment) {
+            out.push_str(&format!("[{segment}]"));
+        } else {
+            out.push_str("['");
+            for c in segment.chars() {
+                match c {
+                    '\\' => out.push_str("\\\\"),
+                    '\'' => out.push_str("\\'"),
+                    c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+                    c => out.push(c),
+                }
+            }
+            out.push_str("']");
+        }
+    }
+    out
+}
+
+/// Convert an RFC 7396 JSON Merge Patch into an equivalent transform.
+///
+/// `null` members become `@jdt.remove` and arrays replace the source array. Below the
+/// root, removals and array replacements go in a root `@jdt.merge` so that the plain
+/// data is still what gets inserted where the source has no object to merge into. A
+/// member name starting with `@jdt.` would be read as syntax, so it fails with
+/// [`JdtError::InvalidPatch`].
+pub fn merge_patch_to_transform(patch: &Value) -> Result<Value, JdtError> {
+    let Some(obj) = patch.as_object() else {
+        return Err(JdtError::TransformNotObject);
+    };
+    diff::check_keys(patch, &mut Vec::new()).map_err(|e| match e {
+        JdtError::ReservedKey(pointer) => {
+            JdtError::InvalidPatch(format!("member {pointer} is reserved for JDT syntax"))
+        }
+        other => other,
+    })?;
+    let patch = obj;
+    let mut transform = Map::new();
+    let mut fixups = Map::new();
+    for (key, value) in patch {
+        match value {
+            Value::Null => {}
+            Value::Array(_) => {
+                transform.insert(key.clone(), replace_array(value));
+            }
+            Value::Object(obj) => {
+                transform.insert(key.clone(), strip_nulls(value));
+                if let Some(fixup) = merge_patch_fixups(obj) {
+                    fixups.insert(key.clone(), Value::Object(fixup));
+                }
+            }
+            _ => {
+                transform.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    let removed = removed_names(patch);
+    if !removed.is_empty() {
+        transform.insert(VERB_REMOVE.to_string(), Value::Array(removed));
+    }
+    if !fixups.is_empty() {
+        transform.insert(VERB_MERGE.to_string(), Value::Object(fixups));
+    }
+    Ok(Value::Object(transform))
+}
+
+fn removed_names(patch: &Map<String, Value>) -> Vec<Value> {
+    patch
+        .iter()
+        .filter(|(_, v)| v.is_null())
+        .map(|(k, _)| Value::String(k.clone()))
+        .collect()
+}
+
+/// The removals and array replacements inside a nested merge patch object.
+fn merge_patch_fixups(patch: &Map<String, Value>) -> Option<Map<String, Value>> {
+    let mut fixup = Map::new();
+    for (key, value) in patch {
+        match value {
+            Value::Array(_) => {
+                fixup.insert(key.clone(), replace_array(value));
+            }
+            Value::Object(obj) => {
+                if let Some(nested) = merge_patch_fixups(obj) {
+                    fixup.insert(key.clone(), Value::Object(nested));
+                }
+            }
+            _ => {}
+        }
+    }
+    let removed = removed_names(patch);
+    if !removed.is_empty() {
+        fixup.insert(VERB_REMOVE.to_string(), Value::Array(removed));
+    }
+    (!fixup.is_empty()).then_some(fixup)
+}
+
+fn replace_array(value: &Value) -> Value {
+    json!({ ATTR_ARRAY_MERGE: "replace", ATTR_VALUE: value })
+}
+
+/// The value a merge patch produces where there is nothing to merge into.
+pub(crate) fn strip_nulls(value: &Value) -> Value {
+    match value {
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k.clone(), strip_nulls(v)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
@@ -0,0 +1,75 @@
+use crate::transform::{apply, JdtError};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+pub(crate) const ATTR_REF: &str = "@jdt.ref";
+const ENV_SCHEME: &str = "env:";
+
+/// Source of values for `{ "@jdt.ref": "<scheme>:<name>" }` references in a transform.
+pub trait ValueProvider {
+    /// Resolve the full reference string (e.g. `env:DB_PASSWORD`), or `None` if unknown.
+    fn resolve(&self, reference: &str) -> Option<Value>;
+}
+
+/// In-memory provider keyed by the full reference string.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryProvider {
+    values: BTreeMap<String, Value>,
+}
+
+impl MemoryProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind `reference` to `value`, returning the previous binding if any.
+    pub fn insert(&mut self, reference: impl Into<String>, value: Value) -> Option<Value> {
+        self.values.insert(reference.into(), value)
+    }
+}
+
+impl ValueProvider for MemoryProvider {
+    fn resolve(&self, reference: &str) -> Option<Value> {
+        self.values.get(reference).cloned()
+    }
+}
+
+/// Resolves `env:NAME` references from the process environment as strings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvProvider;
+
+impl ValueProvider for EnvProvider {
+    fn resolve(&self, reference: &str) -> Option<Value> {
+        let name = reference.strip_prefix(ENV_SCHEME)?;
+        std::env::var(name).ok().map(Value::String)
+    }
+}
+
+/// Apply `transform` to `source` after resolving every `@jdt.ref` through `provider`.
+pub fn apply_with_provider(
+    source: &Value,
+    transform: &Value,
+    provider: &dyn ValueProvider,
+) -> Result<Value, JdtError> {
+    apply(source, &resolve_refs(transform, provider)?)
+}
+
+/// Replace every `{ "@jdt.ref": ... }` object in `transform` with the value from
+/// `provider`, failing with [`JdtError::UnresolvedRef`] if it has none.
+pub fn resolve_refs(transform: &Value, provider: &dyn ValueProvider) -> Result<Value, JdtError> {
+    crate::params::resolve(transform, None, Some(provider), false)
+}
+
+/// The value `provider` gives for the `@jdt.ref` object `obj`.
+pub(crate) fn resolve_reference(
+    obj: &Map<String, Value>,
+    provider: &dyn ValueProvider,
+) -> Result<Value, JdtError> {
+    let reference = obj
+        .get(ATTR_REF)
+        .and_then(Value::as_str)
+        .ok_or(JdtError::AttributeNotString(ATTR_REF))?;
+    provider
+        .resolve(reference)
+        .ok_or_else(|| JdtError::UnresolvedRef(reference.to_string()))
+}
@@ -0,0 +1,989 @@
+use crate::jsonpath::PathItem;
+use crate::patch::Edit;
+use crate::transform::JdtError;
+use serde_json::{Map, Value};
+
+/// Syntax accepted for JSON text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Strict RFC 8259 JSON.
+    #[default]
+    Json,
+    /// JSON with `//` and `/* */` comments and trailing commas (JSONC), as accepted by
+    /// .NET configuration files.
+    Jsonc,
+    /// JSON5: JSONC plus unquoted keys, single-quoted strings, hexadecimal numbers,
+    /// leading `+` and bare decimal points. `Infinity` and `NaN` are rejected, as JSON
+    /// cannot hold them.
+    Json5,
+}
+
+/// Parse JSONC text (see [`InputFormat::Jsonc`]), skipping a leading BOM.
+pub fn parse_jsonc(text: &str) -> Result<Value, JdtError> {
+    Document::parse(text, InputFormat::Jsonc).map(|(_, value)| value)
+}
+
+/// Parse JSON5 text (see [`InputFormat::Json5`]), skipping a leading BOM.
+pub fn parse_json5(text: &str) -> Result<Value, JdtError> {
+    Document::parse(text, InputFormat::Json5).map(|(_, value)| value)
+}
+
+/// Deepest nesting accepted, as for `serde_json`.
+const MAX_DEPTH: usize = 128;
+
+/// JSON text parsed into a tree that keeps every byte of the original formatting, so
+/// edits can be replayed on it and only the changed regions re-emitted.
+pub(crate) struct Document {
+    /// Text before the root value, including a BOM.
+    prefix: String,
+    root: Node,
+    suffix: String,
+    style: Style,
+}
+
+/// Formatting used for inserted text, detected from the source.
+struct Style {
+    /// Whether the source spans several lines; otherwise new text stays on one line.
+    multiline: bool,
+    /// One level of indentation.
+    unit: String,
+    /// Text between a key and its value, including the colon.
+    colon: String,
+    /// Text after a comma between items on one line.
+    separator: String,
+    /// `\r\n` when the source uses it, otherwise `\n`.
+    newline: &'static str,
+}
+
+enum Node {
+    /// The raw text of a string, number or literal.
+    Scalar(String),
+    Object(Container),
+    Array(Container),
+}
+
+struct Container {
+    items: Vec<Item>,
+    trailing_comma: bool,
+    /// Text between the last item (or trailing comma, or opening bracket) and the
+    /// closing bracket.
+    tail: String,
+    /// Indentation of the line holding the opening bracket.
+    indent: String,
+}
+
+struct Item {
+    /// Text between the previous comma (or opening bracket) and the item.
+    before: String,
+    key: Option<Key>,
+    value: Node,
+    /// Text between the value and the following comma or closing bracket.
+    after: String,
+    /// A comment on the same line after the following comma.
+    comment: String,
+}
+
+struct Key {
+    raw: String,
+    name: String,
+    colon: String,
+}
+
+impl Document {
+    /// Parse `text`, returning the tree and the value it holds.
+    pub(crate) fn parse(text: &str, format: InputFormat) -> Result<(Self, Value), JdtError> {
+        let mut parser = Parser {
+            text,
+            pos: 0,
+            depth: 0,
+            format,
+        };
+        let mut prefix = String::new();
+        if text.starts_with('\u{feff}') {
+            parser.pos = '\u{feff}'.len_utf8();
+            prefix.push('\u{feff}');
+        }
+        prefix.push_str(&parser.trivia()?);
+        let (root, value) = parser.value()?;
+        let suffix = parser.trivia()?;
+        if parser.pos < text.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        let style = Style::detect(text, &root);
+        let doc = Self {
+            prefix,
+            root,
+            suffix,
+            style,
+        };
+        Ok((doc, value))
+    }
+
+    /// Replay `edits`, made in order to the parsed value, on the text.
+    pub(crate) fn apply(&mut self, edits: Vec<Edit>) {
+        for edit in edits {
+            self.edit(edit);
+        }
+    }
+
+    pub(crate) fn render(&self) -> String {
+        let mut out = self.prefix.clone();
+        self.root.write(&mut out);
+        out.push_str(&self.suffix);
+        out
+    }
+
+    fn edit(&mut self, edit: Edit) {
+        let style = &self.style;
+        match edit {
+            Edit::Add { path, value } => {
+                let Some((last, parent)) = path.split_last() else {
+                    return;
+                };
+                if let Some(container) = container_at(&mut self.root, parent) {
+                    let (index, key) = match last {
+                        PathItem::Key(k) => (container.items.len(), Some(k.as_str())),
+                        PathItem::Index(i) => (*i, None),
+                    };
+                    container.insert(index.min(container.items.len()), key, &value, style);
+                }
+            }
+            Edit::Remove { path, .. } => {
+                let Some((last, parent)) = path.split_last() else {
+                    return;
+                };
+                if let Some(container) = container_at(&mut self.root, parent) {
+                    if let Some(index) = container.position(last) {
+                        container.remove(index);
+                    }
+                }
+            }
+            Edit::Replace { path, old, value } => {
+                if old == value {
+                    return;
+                }
+                let Some((last, parent)) = path.split_last() else {
+                    self.root = Node::from_value(&value, "", style);
+                    return;
+                };
+                if let Some(container) = container_at(&mut self.root, parent) {
+                    if let Some(index) = container.position(last) {
+                        let indent = container.item_indent(index);
+                        container.items[index].value = Node::from_value(&value, &indent, style);
+                    }
+                }
+            }
+            Edit::Move {
+                from,
+                path,
+                overwritten,
+            } => {
+                let (Some((PathItem::Key(old), parent)), Some(PathItem::Key(new))) =
+                    (from.split_last(), path.last())
+                else {
+                    return;
+                };
+                let Some(container) = container_at(&mut self.root, parent) else {
+                    return;
+                };
+                if overwritten.is_some() {
+                    if let Some(index) = container.position(&PathItem::Key(new.clone())) {
+                        container.remove(index);
+                    }
+                }
+                if let Some(index) = container.position(&PathItem::Key(old.clone())) {
+                    if let Some(key) = container.items[index].key.as_mut() {
+                        key.raw = Value::String(new.clone()).to_string();
+                        key.name = new.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn container_at<'a>(mut node: &'a mut Node, path: &[PathItem]) -> Option<&'a mut Container> {
+    for item in path {
+        let container = node.container_mut()?;
+        let index = container.position(item)?;
+        node = &mut container.items[index].value;
+    }
+    node.container_mut()
+}
+
+impl Style {
+    fn detect(text: &str, root: &Node) -> Self {
+        let first = match root {
+            Node::Object(c) | Node::Array(c) => c.items.first(),
+            Node::Scalar(_) => None,
+        };
+        let unit = first
+            .and_then(|item| line_indent(&item.before))
+            .filter(|indent| !indent.is_empty())
+            .unwrap_or_else(|| "  ".to_string());
+        let multiline = text.trim().contains('\n');
+        let colon = first
+            .and_then(|item| item.key.as_ref())
+            .map(|key| key.colon.clone())
+            .filter(|colon| colon.trim() == ":")
+            .unwrap_or_else(|| if multiline { ": " } else { ":" }.to_string());
+        let separator = match root {
+            Node::Object(c) | Node::Array(c) => c.items.get(1),
+            Node::Scalar(_) => None,
+        }
+        .map(|item| item.before.clone())
+        .filter(|before| before.chars().all(|c| c == ' ' || c == '\t'))
+        .unwrap_or_else(|| {
+            let spaced = colon.ends_with(is_space);
+            if spaced { " " } else { "" }.to_string()
+        });
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        Self {
+            multiline,
+            unit,
+            colon,
+            separator,
+            newline,
+        }
+    }
+}
+
+impl Node {
+    /// Text for `value` as it would appear on a line indented by `indent`: pretty-printed
+    /// with the document's indentation unit, or on one line with its spacing for a
+    /// single-line document.
+    fn from_value(value: &Value, indent: &str, style: &Style) -> Self {
+        let entries: Vec<(Option<&String>, &Value)> = match value {
+            Value::Object(obj) => obj.iter().map(|(k, v)| (Some(k), v)).collect(),
+            Value::Array(items) => items.iter().map(|v| (None, v)).collect(),
+            scalar => return Node::Scalar(scalar.to_string()),
+        };
+        let nl = style.newline;
+        let inner = format!("{indent}{}", style.unit);
+        let (before, separator, tail) = match (style.multiline, entries.is_empty()) {
+            (true, false) => {
+                let before = format!("{nl}{inner}");
+                (before.clone(), before, format!("{nl}{indent}"))
+            }
+            (true, true) => (String::new(), String::new(), String::new()),
+            (false, _) => (String::new(), style.separator.clone(), String::new()),
+        };
+        let items = entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, (key, value))| Item {
+                before: if i == 0 { &before } else { &separator }.clone(),
+                key: key.map(|name| Key {
+                    raw: Value::String(name.clone()).to_string(),
+                    name: name.clone(),
+                    colon: style.colon.clone(),
+                }),
+                value: Node::from_value(value, &inner, style),
+                after: String::new(),
+                comment: String::new(),
+            })
+            .collect();
+        let container = Container {
+            items,
+            trailing_comma: false,
+            tail,
+            indent: indent.to_string(),
+        };
+        if value.is_object() {
+            Node::Object(container)
+        } else {
+            Node::Array(container)
+        }
+    }
+
+    fn container_mut(&mut self) -> Option<&mut Container> {
+        match self {
+            Node::Object(c) | Node::Array(c) => Some(c),
+            Node::Scalar(_) => None,
+        }
+    }
+
+    fn write(&self, out: &mut String) {
+        let (container, open, close) = match self {
+            Node::Scalar(raw) => {
+                out.push_str(raw);
+                return;
+            }
+            Node::Object(c) => (c, '{', '}'),
+            Node::Array(c) => (c, '[', ']'),
+        };
+        out.push(open);
+        for (i, item) in container.items.iter().enumerate() {
+            out.push_str(&item.before);
+            if let Some(key) = &item.key {
+                out.push_str(&key.raw);
+                out.push_str(&key.colon);
+            }
+            item.value.write(out);
+            out.push_str(&item.after);
+            if i + 1 < container.items.len() || container.trailing_comma {
+                out.push(',');
+            }
+            out.push_str(&item.comment);
+        }
+        out.push_str(&container.tail);
+        out.push(close);
+    }
+}
+
+impl Container {
+    /// Index of the item at `item`; keys are unique, as parsing rejects repeated ones.
+    fn position(&self, item: &PathItem) -> Option<usize> {
+        match item {
+            PathItem::Key(k) => self
+                .items
+                .iter()
+                .position(|i| i.key.as_ref().is_some_and(|key| key.name == *k)),
+            PathItem::Index(i) => (*i < self.items.len()).then_some(*i),
+        }
+    }
+
+    /// Indentation of the line holding item `index`.
+    fn item_indent(&self, index: usize) -> String {
+        line_indent(&self.items[index].before).unwrap_or_else(|| self.indent.clone())
+    }
+
+    /// Insert a new item at `index`, formatted like its neighbours.
+    fn insert(&mut self, index: usize, key: Option<&str>, value: &Value, style: &Style) {
+        let nl = style.newline;
+        let (before, after) = if self.items.is_empty() {
+            // Comments in an empty container stay at its start.
+            let tail = std::mem::take(&mut self.tail);
+            let lead = tail.trim_end_matches(is_space);
+            if style.multiline {
+                let before = format!("{lead}{nl}{}{}", self.indent, style.unit);
+                (before, format!("{nl}{}", self.indent))
+            } else {
+                (lead.to_string(), String::new())
+            }
+        } else if index < self.items.len() {
+            let before = line_break(&self.items[index].before, nl);
+            if index == 0 && !before.contains('\n') {
+                // The item that was first now follows a comma.
+                self.items[0].before = style.separator.clone();
+            }
+            (before, String::new())
+        } else {
+            // The line break before the closing bracket moves to the new item, and any
+            // comment after the old last value now follows its comma.
+            let single = self.items.len() == 1;
+            let last = self.items.last_mut().expect("items is not empty");
+            let mut after = std::mem::take(&mut last.after);
+            let tail = after.split_off(after.trim_end_matches(is_space).len());
+            let mut line = line_break(&last.before, nl);
+            if single && !line.contains('\n') {
+                // The only item follows a bracket, not a comma.
+                line = style.separator.clone();
+            }
+            if ends_in_line_comment(&after) && !line.contains('\n') {
+                // A `//` comment runs to the end of its line, so the item starts a new one.
+                line = format!("{nl}{}{}", self.indent, style.unit);
+            }
+            (after + &line, tail)
+        };
+        let colon = self
+            .items
+            .iter()
+            .find_map(|item| item.key.as_ref())
+            .map_or_else(|| style.colon.clone(), |key| key.colon.clone());
+        let indent = line_indent(&before).unwrap_or_else(|| self.indent.clone());
+        let item = Item {
+            before,
+            key: key.map(|name| Key {
+                raw: Value::String(name.to_string()).to_string(),
+                name: name.to_string(),
+                colon,
+            }),
+            value: Node::from_value(value, &indent, style),
+            after,
+            comment: String::new(),
+        };
+        self.items.insert(index, item);
+    }
+
+    fn remove(&mut self, index: usize) {
+        let removed = self.items.remove(index);
+        if self.items.is_empty() {
+            self.trailing_comma = false;
+            if is_blank(&self.tail) {
+                self.tail.clear();
+            }
+        } else if index == self.items.len() && !self.trailing_comma {
+            // The new last item loses its comma, so its comment joins the text after it.
+            // The removed item's own comments go with it, leaving the line break.
+            let last = &mut self.items[index - 1];
+            let comment = std::mem::take(&mut last.comment);
+            last.after.push_str(&comment);
+            let after = removed.after.trim_end_matches(is_space);
+            last.after.push_str(&removed.after[after.len()..]);
+        }
+    }
+}
+
+/// Indentation after the last line break in `trivia`, or `None` when it has none.
+fn line_indent(trivia: &str) -> Option<String> {
+    let (_, line) = trivia.rsplit_once('\n')?;
+    Some(line.chars().take_while(|c| is_space(*c)).collect())
+}
+
+/// The whitespace that starts a new item placed like one preceded by `before`.
+fn line_break(before: &str, newline: &str) -> String {
+    match line_indent(before) {
+        Some(indent) => format!("{newline}{indent}"),
+        None => before.chars().rev().take_while(|c| is_space(*c)).collect(),
+    }
+}
+
+/// Whether trivia `text` ends inside a `//` comment.
+fn ends_in_line_comment(mut text: &str) -> bool {
+    while let Some(start) = text.find('/') {
+        let rest = &text[start..];
+        if rest.starts_with("//") {
+            match rest.find(['\r', '\n']) {
+                Some(end) => text = &rest[end..],
+                None => return true,
+            }
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            // Comments in parsed trivia are terminated.
+            text = body.split_once("*/").map_or("", |(_, after)| after);
+        } else {
+            text = &rest[1..];
+        }
+    }
+    false
+}
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+fn is_blank(text: &str) -> bool {
+    text.chars().all(is_space)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    depth: usize,
+    format: InputFormat,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> JdtError {
+        let consumed = &self.text[..self.pos];
+        let line = consumed.matches('\n').count() + 1;
+        let column = consumed
+            .rsplit_once('\n')
+            .map_or(consumed, |(_, l)| l)
+            .chars()
+            .count()
+            + 1;
+        JdtError::Syntax {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn json5(&self) -> bool {
+        self.format == InputFormat::Json5
+    }
+
+    fn trivia(&mut self) -> Result<String, JdtError> {
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.pos += 1,
+                Some(b'/') if self.comment()? => {}
+                _ => match self.peek_char() {
+                    Some(c) if self.json5() && is_json5_space(c) => self.pos += c.len_utf8(),
+                    _ => break,
+                },
+            }
+        }
+        Ok(self.text[start..self.pos].to_string())
+    }
+
+    /// Trivia up to the end of the line when it holds a comment; otherwise nothing is
+    /// consumed.
+    fn line_comment(&mut self) -> Result<String, JdtError> {
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t') => self.pos += 1,
+                Some(b'/') if self.comment()? => {}
+                _ => break,
+            }
+        }
+        let text = &self.text[start..self.pos];
+        if matches!(self.peek(), Some(b'\n' | b'\r')) && !is_blank(text) {
+            return Ok(text.to_string());
+        }
+        self.pos = start;
+        Ok(String::new())
+    }
+
+    /// Skip a comment starting at the current `/`, in JSONC and JSON5 modes.
+    fn comment(&mut self) -> Result<bool, JdtError> {
+        if self.format == InputFormat::Json {
+            return Ok(false);
+        }
+        let rest = &self.text[self.pos..];
+        if rest.starts_with("//") {
+            self.pos += rest.find(['\r', '\n']).unwrap_or(rest.len());
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            let Some(end) = body.find("*/") else {
+                return Err(self.error("unterminated comment"));
+            };
+            self.pos += end + 4;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn expect(&mut self, byte: u8, message: &str) -> Result<(), JdtError> {
+        if self.peek() != Some(byte) {
+            return Err(self.error(message));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<(Node, Value), JdtError> {
+        match self.peek() {
+            Some(b'{') => self.container(true),
+            Some(b'[') => self.container(false),
+            Some(b'"') => self.string_value(),
+            Some(b'\'') if self.json5() => self.string_value(),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b'+' | b'.' | b'I' | b'N') if self.json5() => self.number(),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<(Node, Value), JdtError> {
+        if !self.text[self.pos..].starts_with(word) {
+            return Err(self.error("expected a value"));
+        }
+        self.pos += word.len();
+        Ok((Node::Scalar(word.to_string()), value))
+    }
+
+    fn string_value(&mut self) -> Result<(Node, Value), JdtError> {
+        let (raw, s) = self.string()?;
+        Ok((Node::Scalar(raw), Value::String(s)))
+    }
+
+    /// A string's raw text and value.
+    fn string(&mut self) -> Result<(String, String), JdtError> {
+        if self.json5() {
+            return self.string5();
+        }
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    // Step over an escaped quote or backslash; the rest are checked below.
+                    self.pos += 1;
+                    if matches!(self.peek(), Some(b'"' | b'\\')) {
+                        self.pos += 1;
+                    }
+                }
+                Some(b) if b < 0x20 => return Err(self.error("control character in string")),
+                Some(_) => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        let raw = &self.text[start..self.pos];
+        match serde_json::from_str::<String>(raw) {
+            Ok(s) => Ok((raw.to_string(), s)),
+            Err(_) => {
+                self.pos = start;
+                Err(self.error("invalid escape in string"))
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<(Node, Value), JdtError> {
+        if self.json5() {
+            return self.number5();
+        }
+        let start = self.pos;
+        let digits = |p: &mut Self| {
+            let from = p.pos;
+            while p.peek().is_some_and(|b| b.is_ascii_digit()) {
+                p.pos += 1;
+            }
+            p.pos > from
+        };
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        let int_start = self.pos;
+        if !digits(self) {
+            return Err(self.error("invalid number"));
+        }
+        if self.text.as_bytes()[int_start] == b'0' && self.pos - int_start > 1 {
+            self.pos = int_start;
+            return Err(self.error("leading zero in number"));
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if !digits(self) {
+                return Err(self.error("invalid number"));
+            }
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                return Err(self.error("invalid number"));
+            }
+        }
+        let raw = &self.text[start..self.pos];
+        match serde_json::from_str(raw) {
+            Ok(value) => Ok((Node::Scalar(raw.to_string()), value)),
+            Err(_) => {
+                self.pos = start;
+                Err(self.error("number out of range"))
+            }
+        }
+    }
+
+    fn container(&mut self, object: bool) -> Result<(Node, Value), JdtError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        self.depth += 1;
+        let line_start = self.text[..self.pos].rfind('\n').map_or(0, |i| i + 1);
+        let indent = self.text[line_start..self.pos]
+            .chars()
+            .take_while(|c| matches!(c, ' ' | '\t'))
+            .collect();
+        let close = if object { b'}' } else { b']' };
+        self.pos += 1;
+        let mut container = Container {
+            items: Vec::new(),
+            trailing_comma: false,
+            tail: String::new(),
+            indent,
+        };
+        let mut map = Map::new();
+        let mut array = Vec::new();
+        loop {
+            let before = self.trivia()?;
+            if self.peek() == Some(close) {
+                if !container.items.is_empty() {
+                    if self.format == InputFormat::Json {
+                        return Err(self.error("trailing comma"));
+                    }
+                    container.trailing_comma = true;
+                }
+                container.tail = before;
+                break;
+            }
+            let key = if object {
+                let key_start = self.pos;
+                let (raw, name) = match self.peek() {
+                    Some(b'"') => self.string()?,
+                    Some(b'\'') if self.json5() => self.string()?,
+                    Some(_) if self.json5() => self.identifier()?,
+                    _ => return Err(self.error("expected a string key")),
+                };
+                // .NET configuration rejects these too, and edits could not tell them apart.
+                if map.contains_key(&name) {
+                    self.pos = key_start;
+                    return Err(self.error("duplicate key"));
+                }
+                let start = self.pos;
+                self.trivia()?;
+                self.expect(b':', "expected ':'")?;
+                self.trivia()?;
+                let colon = self.text[start..self.pos].to_string();
+                Some(Key { raw, name, colon })
+            } else {
+                None
+            };
+            let (node, value) = self.value()?;
+            match &key {
+                Some(key) => {
+                    map.insert(key.name.clone(), value);
+                }
+                None => array.push(value),
+            }
+            let after = self.trivia()?;
+            container.items.push(Item {
+                before,
+                key,
+                value: node,
+                after,
+                comment: String::new(),
+            });
+            match self.peek() {
+                Some(b',') => {
+                    self.pos += 1;
+                    let comment = self.line_comment()?;
+                    container.items.last_mut().expect("just pushed").comment = comment;
+                }
+                Some(b) if b == close => break,
+                Some(_) => return Err(self.error("expected ',' or a closing bracket")),
+                None => return Err(self.error("unexpected end of input")),
+            }
+        }
+        self.pos += 1;
+        self.depth -= 1;
+        Ok(if object {
+            (Node::Object(container), Value::Object(map))
+        } else {
+            (Node::Array(container), Value::Array(array))
+        })
+    }
+}
+
+/// JSON5 tokens.
+impl Parser<'_> {
+    fn string5(&mut self) -> Result<(String, String), JdtError> {
+        let start = self.pos;
+        let quote = self.text.as_bytes()[start] as char;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek_char() {
+                None => return Err(self.error("unterminated string")),
+                Some(c) if c == quote => break,
+                Some('\n' | '\r') => return Err(self.error("line break in string")),
+                Some('\\') => self.escape5(&mut out)?,
+                Some(c) => {
+                    out.push(c);
+                    self.pos += c.len_utf8();
+                }
+            }
+        }
+        self.pos += 1;
+        Ok((self.text[start..self.pos].to_string(), out))
+    }
+
+    /// Decode the escape sequence at the cursor into `out`.
+    fn escape5(&mut self, out: &mut String) -> Result<(), JdtError> {
+        let start = self.pos;
+        self.pos += 1;
+        let Some(c) = self.peek_char() else {
+            return Err(self.error("unterminated string"));
+        };
+        self.pos += c.len_utf8();
+        let decoded = match c {
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\u{b}',
+            '0' if !self.peek().is_some_and(|b| b.is_ascii_digit()) => '\0',
+            '0'..='9' => {
+                self.pos = start;
+                return Err(self.error("invalid escape in string"));
+            }
+            'x' => {
+                let code = self.hex(2, start)?;
+                char::from_u32(code).expect("two hex digits are a valid char")
+            }
+            'u' => self.unicode_escape(start)?,
+            // A line continuation.
+            '\r' => {
+                if self.peek() == Some(b'\n') {
+                    self.pos += 1;
+                }
+                return Ok(());
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => return Ok(()),
+            other => other,
+        };
+        out.push(decoded);
+        Ok(())
+    }
+
+    /// The character of a `\\u` escape whose digits start at the cursor, joining a
+    /// surrogate pair.
+    fn unicode_escape(&mut self, start: usize) -> Result<char, JdtError> {
+        let high = self.hex(4, start)?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.text[self.pos..].starts_with("\\u") {
+                self.pos = start;
+                return Err(self.error("unpaired surrogate in escape"));
+            }
+            self.pos += 2;
+            let low = self.hex(4, start)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                self.pos = start;
+                return Err(self.error("unpaired surrogate in escape"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| {
+            self.pos = start;
+            self.error("unpaired surrogate in escape")
+        })
+    }
+
+    /// `len` hex digits at the cursor; an error points at the escape starting at `start`.
+    fn hex(&mut self, len: usize, start: usize) -> Result<u32, JdtError> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + len)
+            .filter(|d| d.bytes().all(|b| b.is_ascii_hexdigit()));
+        match digits {
+            Some(digits) => {
+                self.pos += len;
+                Ok(u32::from_str_radix(digits, 16).expect("checked hex digits"))
+            }
+            None => {
+                self.pos = start;
+                Err(self.error("invalid escape in string"))
+            }
+        }
+    }
+
+    /// An unquoted object key: its raw text and name.
+    fn identifier(&mut self) -> Result<(String, String), JdtError> {
+        let start = self.pos;
+        let mut name = String::new();
+        while let Some(c) = self.peek_char() {
+            if c == '\\' {
+                let escape = self.pos;
+                if !self.text[self.pos..].starts_with("\\u") {
+                    return Err(self.error("invalid escape in key"));
+                }
+                self.pos += 2;
+                let c = self.unicode_escape(escape)?;
+                if !is_identifier_char(c, name.is_empty()) {
+                    self.pos = escape;
+                    return Err(self.error("invalid character in key"));
+                }
+                name.push(c);
+            } else if is_identifier_char(c, name.is_empty()) {
+                self.pos += c.len_utf8();
+                name.push(c);
+            } else {
+                break;
+            }
+        }
+        if name.is_empty() {
+            return Err(self.error("expected a key"));
+        }
+        Ok((self.text[start..self.pos].to_string(), name))
+    }
+
+    fn number5(&mut self) -> Result<(Node, Value), JdtError> {
+        let start = self.pos;
+        let negative = self.peek() == Some(b'-');
+        if matches!(self.peek(), Some(b'+' | b'-')) {
+            self.pos += 1;
+        }
+        let rest = &self.text[self.pos..];
+        if rest.starts_with("Infinity") || rest.starts_with("NaN") {
+            self.pos = start;
+            return Err(self.error("Infinity and NaN cannot be represented in JSON"));
+        }
+        let value = if let Some(hex) = rest.strip_prefix("0x").or(rest.strip_prefix("0X")) {
+            let len = hex.bytes().take_while(u8::is_ascii_hexdigit).count();
+            self.pos += 2 + len;
+            if len == 0 {
+                return Err(self.error("invalid number"));
+            }
+            let magnitude = u64::from_str_radix(&hex[..len], 16).ok();
+            match magnitude {
+                Some(m) if !negative => Some(Value::from(m)),
+                Some(m) => 0i64.checked_sub_unsigned(m).map(Value::from),
+                None => None,
+            }
+        } else {
+            let digits = |p: &mut Self| {
+                let from = p.pos;
+                while p.peek().is_some_and(|b| b.is_ascii_digit()) {
+                    p.pos += 1;
+                }
+                &p.text[from..p.pos]
+            };
+            let int = digits(self);
+            if int.len() > 1 && int.starts_with('0') {
+                self.pos = start;
+                return Err(self.error("leading zero in number"));
+            }
+            let frac = if self.peek() == Some(b'.') {
+                self.pos += 1;
+                Some(digits(self))
+            } else {
+                None
+            };
+            if int.is_empty() && frac.unwrap_or_default().is_empty() {
+                return Err(self.error("invalid number"));
+            }
+            let exp_start = self.pos;
+            if matches!(self.peek(), Some(b'e' | b'E')) {
+                self.pos += 1;
+                if matches!(self.peek(), Some(b'+' | b'-')) {
+                    self.pos += 1;
+                }
+                if digits(self).is_empty() {
+                    return Err(self.error("invalid number"));
+                }
+            }
+            // Respell as JSON, keeping a decimal point so the value stays a float.
+            let mut json = String::from(if negative { "-" } else { "" });
+            json.push_str(if int.is_empty() { "0" } else { int });
+            if let Some(frac) = frac {
+                json.push('.');
+                json.push_str(if frac.is_empty() { "0" } else { frac });
+            }
+            json.push_str(&self.text[exp_start..self.pos]);
+            serde_json::from_str(&json).ok()
+        };
+        match value {
+            Some(value) => Ok((Node::Scalar(self.text[start..self.pos].to_string()), value)),
+            None => {
+                self.pos = start;
+                Err(self.error("number out of range"))
+            }
+        }
+    }
+}
+
+fn is_json5_space(c: char) -> bool {
+    matches!(
+        c,
+        '\u{b}' | '\u{c}' | '\u{a0}' | '\u{feff}' | '\u{1680}' | '\u{2000}'
+            ..='\u{200a}' | '\u{2028}' | '\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}'
+    )
+}
+
+fn is_identifier_char(c: char, first: bool) -> bool {
+    c == '$'
+        || c == '_'
+        || c.is_alphabetic()
+        || (!first && (c.is_numeric() || c == '\u{200c}' || c == '\u{200d}'))
+}
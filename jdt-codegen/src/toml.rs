@@ -0,0 +1,167 @@
+use crate::jsonpath::PathItem;
+use crate::patch;
+use crate::transform::JdtError;
+use ::toml::value::{Datetime, Table, Value as Toml};
+use serde_json::{Map, Number, Value};
+use std::collections::HashSet;
+
+/// Parse a TOML document into an object. Dates and times become RFC 3339 strings;
+/// `inf` and `nan` are rejected.
+pub fn parse_toml(text: &str) -> Result<Value, JdtError> {
+    Ok(Document::parse(text)?.value)
+}
+
+/// Render `value` as a TOML document. It must be an object, and may not hold nulls,
+/// integers above `i64::MAX`, or top-level arrays mixing types.
+pub fn to_toml(value: &Value) -> Result<String, JdtError> {
+    render(value, &HashSet::new())
+}
+
+/// A parsed TOML document, remembering where it held dates and times. An array that held
+/// one is also remembered, by its pointer followed by `/-`.
+pub(crate) struct Document {
+    pub(crate) value: Value,
+    datetimes: HashSet<String>,
+}
+
+impl Document {
+    pub(crate) fn parse(text: &str) -> Result<Self, JdtError> {
+        let table: Table = text.parse().map_err(|e: ::toml::de::Error| {
+            let at = e.span().map_or(0, |span| span.start);
+            let consumed = &text[..at];
+            let line = consumed.matches('\n').count() + 1;
+            let column = consumed.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+            let message = e.message();
+            JdtError::Toml(format!("line {line}, column {column}: {message}"))
+        })?;
+        let mut path = Vec::new();
+        let mut datetimes = HashSet::new();
+        let value = to_json(Toml::Table(table), &mut path, &mut datetimes).map_err(|what| {
+            let at = patch::pointer(&path);
+            JdtError::Toml(format!("at \"{at}\": {what}"))
+        })?;
+        Ok(Self { value, datetimes })
+    }
+
+    /// Render `value`, writing a string as a date-time where this document held one, or
+    /// in an array that held one, and the string is still a valid TOML date-time.
+    pub(crate) fn render(&self, value: &Value) -> Result<String, JdtError> {
+        render(value, &self.datetimes)
+    }
+}
+
+fn render(value: &Value, datetimes: &HashSet<String>) -> Result<String, JdtError> {
+    let mut path = Vec::new();
+    let table = match from_json(value, &mut path, datetimes) {
+        Ok(Toml::Table(table)) => Ok(table),
+        Ok(_) => Err("a TOML document must be a table".to_string()),
+        Err(reason) => Err(reason),
+    }
+    .map_err(|reason| JdtError::TomlValue {
+        path: patch::pointer(&path),
+        reason,
+    })?;
+    ::toml::to_string(&table).map_err(|e| JdtError::TomlValue {
+        path: String::new(),
+        reason: e.to_string(),
+    })
+}
+
+/// Convert `toml`, leaving `path` at the offending node on error.
+fn to_json(
+    toml: Toml,
+    path: &mut Vec<PathItem>,
+    datetimes: &mut HashSet<String>,
+) -> Result<Value, String> {
+    Ok(match toml {
+        Toml::String(s) => Value::String(s),
+        Toml::Integer(i) => Value::from(i),
+        Toml::Float(f) => {
+            Value::Number(Number::from_f64(f).ok_or_else(|| format!("{f} has no JSON equivalent"))?)
+        }
+        Toml::Boolean(b) => Value::Bool(b),
+        Toml::Datetime(dt) => {
+            if let Some((PathItem::Index(_), parent)) = path.split_last() {
+                datetimes.insert(format!("{}/-", patch::pointer(parent)));
+            }
+            datetimes.insert(patch::pointer(path));
+            Value::String(dt.to_string())
+        }
+        Toml::Array(items) => {
+            let mut array = Vec::with_capacity(items.len());
+            for (i, item) in items.into_iter().enumerate() {
+                path.push(PathItem::Index(i));
+                array.push(to_json(item, path, datetimes)?);
+                path.pop();
+            }
+            Value::Array(array)
+        }
+        Toml::Table(table) => {
+            let mut obj = Map::new();
+            for (key, value) in table {
+                path.push(PathItem::Key(key.clone()));
+                let value = to_json(value, path, datetimes)?;
+                path.pop();
+                obj.insert(key, value);
+            }
+            Value::Object(obj)
+        }
+    })
+}
+
+/// Convert `value`, leaving `path` at the offending node on error.
+fn from_json(
+    value: &Value,
+    path: &mut Vec<PathItem>,
+    datetimes: &HashSet<String>,
+) -> Result<Toml, String> {
+    Ok(match value {
+        Value::Null => return Err("TOML has no null".to_string()),
+        Value::Bool(b) => Toml::Boolean(*b),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => Toml::Integer(i),
+            (None, Some(f)) if !n.is_u64() => Toml::Float(f),
+            _ => return Err(format!("{n} is above the largest TOML integer")),
+        },
+        Value::String(s) => {
+            let was_datetime = !datetimes.is_empty()
+                && (datetimes.contains(&patch::pointer(path))
+                    || matches!(path.split_last(), Some((PathItem::Index(_), parent))
+                        if datetimes.contains(&format!("{}/-", patch::pointer(parent)))));
+            match was_datetime.then(|| s.parse::<Datetime>()) {
+                Some(Ok(dt)) => Toml::Datetime(dt),
+                _ => Toml::String(s.clone()),
+            }
+        }
+        Value::Array(items) => {
+            let mut array: Vec<Toml> = Vec::with_capacity(items.len());
+            for (i, item) in items.iter().enumerate() {
+                path.push(PathItem::Index(i));
+                let item = from_json(item, path, datetimes)?;
+                // Only arrays directly in the document's table must hold one type.
+                if let Some(first) = array.first().filter(|_| path.len() == 2) {
+                    if first.type_str() != item.type_str() {
+                        return Err(format!(
+                            "a top-level array cannot mix {} and {}",
+                            first.type_str(),
+                            item.type_str()
+                        ));
+                    }
+                }
+                array.push(item);
+                path.pop();
+            }
+            Toml::Array(array)
+        }
+        Value::Object(obj) => {
+            let mut table = Table::new();
+            for (key, value) in obj {
+                path.push(PathItem::Key(key.clone()));
+                let value = from_json(value, path, datetimes)?;
+                path.pop();
+                table.insert(key.clone(), value);
+            }
+            Toml::Table(table)
+        }
+    })
+}
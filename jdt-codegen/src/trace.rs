@@ -0,0 +1,61 @@
+use serde_json::{json, Value};
+
+/// Longest string value quoted in full by an old value summary.
+const SUMMARY_MAX_CHARS: usize = 40;
+
+/// One operation performed while applying a transform; see
+/// [`apply_traced`](crate::apply_traced).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEvent {
+    /// `remove`, `replace`, `rename`, `merge`, or `default` for the default transformation.
+    pub verb: &'static str,
+    /// JSON Pointer to the verb's value (or the default key) in the transform.
+    pub location: String,
+    /// The `@jdt.path` selector of an attributed call.
+    pub selector: Option<String>,
+    /// JSON Pointers of the source nodes the operation targets.
+    pub matched: Vec<String>,
+    /// Summary of the value at each matched path before the operation; `None` when absent.
+    pub old: Vec<Option<String>>,
+    /// The value the operation writes: the replacement, merged value or new name.
+    pub new: Option<Value>,
+    /// Whether the operation, including any nested ones, changed the document.
+    pub changed: bool,
+}
+
+impl TraceEvent {
+    /// The event as a JSON object with the same field names.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "verb": self.verb,
+            "location": self.location,
+            "selector": self.selector,
+            "matched": self.matched,
+            "old": self.old,
+            "new": self.new,
+            "changed": self.changed,
+        })
+    }
+}
+
+/// A short description of `value`: scalars as JSON, long strings truncated, and
+/// containers by size.
+pub(crate) fn summarize(value: &Value) -> String {
+    match value {
+        Value::Object(o) => format!("{{{} {}}}", o.len(), plural(o.len(), "key")),
+        Value::Array(a) => format!("[{} {}]", a.len(), plural(a.len(), "item")),
+        Value::String(s) if s.chars().count() > SUMMARY_MAX_CHARS => {
+            let head: String = s.chars().take(SUMMARY_MAX_CHARS).collect();
+            format!("\"{head}…\"")
+        }
+        other => other.to_string(),
+    }
+}
+
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 {
+        word.to_string()
+    } else {
+        format!("{word}s")
+    }
+}
@@ -1,5 +1,14 @@
-use crate::jsonpath::{JsonPath, JsonPathError, PathItem};
+use crate::jsonpath::{find_key, keys_equal_ignore_case, JsonPath, JsonPathError, PathItem};
+use crate::order;
+use crate::params::{self, Params};
+use crate::patch::{self, Edit};
+use crate::provider::ValueProvider;
+use crate::text::{Document, InputFormat};
+use crate::trace::{self, TraceEvent};
 use serde_json::Value;
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -14,32 +23,561 @@ pub enum JdtError {
     MissingAttribute(&'static str),
     #[error("attribute must be string: {0}")]
     AttributeNotString(&'static str),
+    #[error("attribute must be boolean: {0}")]
+    AttributeNotBool(&'static str),
+    #[error("invalid value for {0}: {1}")]
+    InvalidAttributeValue(&'static str, String),
     #[error("rename target is not a property (cannot rename root/array element)")]
     RenameNotProperty,
+    #[error("rename target already exists: {0}")]
+    RenameCollision(String),
     #[error("cannot remove/replace root with this operation")]
     RootOperationNotAllowed,
     #[error("unknown @jdt verb: {0}")]
     UnknownVerb(String),
+    #[error("missing parameter: {0}")]
+    MissingParam(String),
+    #[error("parameter {name} must be {expected}")]
+    ParamType { name: String, expected: String },
+    #[error("unresolved reference: {0}")]
+    UnresolvedRef(String),
+    #[error("transform layer {index}: {source}")]
+    Layer { index: usize, source: Box<JdtError> },
+    #[error("transforms cannot be composed: {}", .0.join("; "))]
+    NotComposable(Vec<String>),
+    #[error("invalid patch: {0}")]
+    InvalidPatch(String),
+    #[error("unsupported patch operation: {0}")]
+    UnsupportedPatchOp(String),
+    #[error("selector matched nothing: {0}")]
+    NoMatch(String),
+    #[error("selector {selector} matched {actual} nodes, expected {expected}")]
+    UnexpectedMatchCount {
+        selector: String,
+        expected: String,
+        actual: usize,
+    },
+    #[error("invalid JSON at line {line}, column {column}: {message}")]
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("invalid YAML: {0}")]
+    Yaml(String),
+    #[error("document {index}: {source}")]
+    Document { index: usize, source: Box<JdtError> },
+    #[error("invalid TOML: {0}")]
+    Toml(String),
+    #[error("value at \"{path}\" cannot be written as TOML: {reason}")]
+    TomlValue { path: String, reason: String },
+    #[error("configuration key \"{key}\": {reason}")]
+    ConfigKey { key: String, reason: String },
+    #[error("key at \"{0}\" is reserved for JDT syntax")]
+    ReservedKey(String),
 }
 
-const VERB_REMOVE: &str = "@jdt.remove";
-const VERB_REPLACE: &str = "@jdt.replace";
-const VERB_RENAME: &str = "@jdt.rename";
-const VERB_MERGE: &str = "@jdt.merge";
+pub(crate) const VERB_REMOVE: &str = "@jdt.remove";
+pub(crate) const VERB_REPLACE: &str = "@jdt.replace";
+pub(crate) const VERB_RENAME: &str = "@jdt.rename";
+pub(crate) const VERB_MERGE: &str = "@jdt.merge";
 
-const ATTR_PATH: &str = "@jdt.path";
-const ATTR_VALUE: &str = "@jdt.value";
+pub(crate) const ATTR_PATH: &str = "@jdt.path";
+pub(crate) const ATTR_VALUE: &str = "@jdt.value";
+const ATTR_MERGE_KEY: &str = "@jdt.mergeKey";
+const ATTR_REMOVE_UNMATCHED: &str = "@jdt.removeUnmatched";
+pub(crate) const ATTR_ARRAY_MERGE: &str = "@jdt.arrayMerge";
+pub(crate) const ATTR_ON_COLLISION: &str = "@jdt.onCollision";
+pub(crate) const ATTR_EXPECT: &str = "@jdt.expect";
+
+/// How the elements of a transform array are combined with a source array.
+///
+/// A transform node can override the policy with `@jdt.arrayMerge` (or `@jdt.mergeKey`
+/// for [`ArrayMerge::Keyed`]); otherwise [`ApplyOptions::array_merge`] is used.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ArrayMerge {
+    /// Append transform elements to the source array (JDT default).
+    #[default]
+    Append,
+    /// Replace the source array with the transform array.
+    Replace,
+    /// Append only transform elements not already present in the source array.
+    Union,
+    /// Merge element `i` of the transform into element `i` of the source, appending extras.
+    Positional,
+    /// Match object elements on `key`, deep-merge matches and append the rest.
+    Keyed { key: String, remove_unmatched: bool },
+}
+
+/// What a rename does when the new name already exists on the parent object.
+///
+/// A rename can override the policy with `@jdt.onCollision`; otherwise
+/// [`ApplyOptions::rename_collision`] is used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenameCollision {
+    /// Replace the existing value with the renamed one (JDT default).
+    #[default]
+    Overwrite,
+    /// Fail with [`JdtError::RenameCollision`].
+    Error,
+    /// Leave both keys untouched.
+    KeepExisting,
+    /// Deep-merge the renamed value into the existing one.
+    Merge,
+}
+
+/// Options controlling how a transform is applied.
+#[derive(Clone, Default)]
+pub struct ApplyOptions {
+    /// Array policy for nodes that do not specify one.
+    pub array_merge: ArrayMerge,
+    /// Collision policy for renames that do not specify one.
+    pub rename_collision: RenameCollision,
+    /// Use RFC 7396 (JSON Merge Patch) semantics in the default transformation: `null`
+    /// removes the key and arrays replace the source array unless the node sets
+    /// `@jdt.arrayMerge`. Verbs, including `@jdt.merge`, are unaffected.
+    pub merge_patch: bool,
+    /// Fail with [`JdtError::NoMatch`] when a `@jdt.path` selector matches no node.
+    pub strict: bool,
+    /// Syntax of the source text given to [`ApplyOptions::apply_text`].
+    pub input_format: InputFormat,
+    /// Match object keys ignoring case, as .NET configuration does, in the default
+    /// merge, verbs and `@jdt.path` selectors. A matched source key keeps its spelling;
+    /// new keys and rename targets are spelled as in the transform.
+    pub case_insensitive: bool,
+    /// Values for the transform's `@jdt.param` and `${name}` references. Without them
+    /// strings such as `"${HOME}/logs"` stay literal, and a `@jdt.param` object fails with
+    /// [`JdtError::MissingParam`].
+    pub params: Option<Params>,
+    /// Source of values for the transform's `@jdt.ref` references; without one they fail
+    /// with [`JdtError::UnresolvedRef`].
+    pub provider: Option<Arc<dyn ValueProvider + Send + Sync>>,
+}
+
+impl fmt::Debug for ApplyOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApplyOptions")
+            .field("array_merge", &self.array_merge)
+            .field("rename_collision", &self.rename_collision)
+            .field("merge_patch", &self.merge_patch)
+            .field("strict", &self.strict)
+            .field("input_format", &self.input_format)
+            .field("case_insensitive", &self.case_insensitive)
+            .field("params", &self.params)
+            .field("provider", &self.provider.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl ApplyOptions {
+    /// `transform` with its references resolved through [`params`](Self::params) and
+    /// [`provider`](Self::provider); every `apply` method does this first.
+    pub fn resolve<'t>(&self, transform: &'t Value) -> Result<Cow<'t, Value>, JdtError> {
+        if self.params.is_none() && self.provider.is_none() {
+            params::reject_references(transform)?;
+            return Ok(Cow::Borrowed(transform));
+        }
+        let provider = self.provider.as_deref().map(|p| p as &dyn ValueProvider);
+        params::resolve(transform, self.params.as_ref(), provider, true).map(Cow::Owned)
+    }
+
+    pub fn apply(&self, source: &Value, transform: &Value) -> Result<Value, JdtError> {
+        let transform = self.resolve(transform)?;
+        let mut out = source.clone();
+        process_transform(&mut out, &transform, true, &mut Context::new(self))?;
+        Ok(out)
+    }
+
+    /// Apply `transform` to `document` without copying it. If the transform fails,
+    /// every change already made is rolled back and `document` is left untouched.
+    pub fn apply_in_place(&self, document: &mut Value, transform: &Value) -> Result<(), JdtError> {
+        let transform = &*self.resolve(transform)?;
+        let mut cx = Context::recording(self);
+        let result = process_transform(document, transform, true, &mut cx);
+        if result.is_err() {
+            patch::undo(document, cx.into_edits());
+        }
+        result
+    }
+
+    /// Apply `transform` and also return an RFC 6902 JSON Patch (a JSON array of
+    /// operations) that turns `source` into the result.
+    pub fn apply_with_patch(
+        &self,
+        source: &Value,
+        transform: &Value,
+    ) -> Result<(Value, Value), JdtError> {
+        let transform = &*self.resolve(transform)?;
+        let mut out = source.clone();
+        let mut cx = Context::recording(self);
+        process_transform(&mut out, transform, true, &mut cx)?;
+        Ok((out, patch::to_json_patch(&cx.into_edits())))
+    }
+
+    /// Apply `transform` to the JSON text `source`, keeping its formatting: only the
+    /// changed values, keys and members are rewritten, and every other byte, including
+    /// JSONC comments, is kept.
+    pub fn apply_text(&self, source: &str, transform: &Value) -> Result<String, JdtError> {
+        let transform = &*self.resolve(transform)?;
+        let (mut doc, mut out) = Document::parse(source, self.input_format)?;
+        let mut cx = Context::recording(self);
+        process_transform(&mut out, transform, true, &mut cx)?;
+        doc.apply(cx.into_edits());
+        Ok(doc.render())
+    }
+
+    /// Apply `transform` to every document of the YAML stream `source`, returning the
+    /// resulting stream. Empty documents are kept as they are.
+    #[cfg(feature = "yaml")]
+    pub fn apply_yaml(&self, source: &str, transform: &Value) -> Result<String, JdtError> {
+        let documents = crate::yaml::parse_yaml(source)?
+            .iter()
+            .enumerate()
+            .map(|(index, document)| match document {
+                Value::Null => Ok(Value::Null),
+                document => self
+                    .apply(document, transform)
+                    .map_err(|e| JdtError::Document {
+                        index,
+                        source: Box::new(e),
+                    }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        crate::yaml::to_yaml(&documents)
+    }
+
+    /// Apply `transform` to the TOML document `source`, returning the result as TOML.
+    /// Values that were dates or times are written back as such while they still hold
+    /// a valid one.
+    #[cfg(feature = "toml")]
+    pub fn apply_toml(&self, source: &str, transform: &Value) -> Result<String, JdtError> {
+        let document = crate::toml::Document::parse(source)?;
+        let out = self.apply(&document.value, transform)?;
+        document.render(&out)
+    }
+
+    /// Apply `transform` and also return a JSON Patch that turns the result back into
+    /// `source`, restoring removed values, original arrays and renamed keys.
+    pub fn apply_with_inverse(
+        &self,
+        source: &Value,
+        transform: &Value,
+    ) -> Result<(Value, Value), JdtError> {
+        let transform = &*self.resolve(transform)?;
+        let mut out = source.clone();
+        let mut cx = Context::recording(self);
+        process_transform(&mut out, transform, true, &mut cx)?;
+        Ok((out, patch::to_json_patch(&patch::invert(&cx.into_edits()))))
+    }
+
+    /// Apply `transform` and also return every operation performed, in order.
+    pub fn apply_traced(
+        &self,
+        source: &Value,
+        transform: &Value,
+    ) -> Result<(Value, Vec<TraceEvent>), JdtError> {
+        let transform = &*self.resolve(transform)?;
+        let mut out = source.clone();
+        let mut cx = Context::tracing(self);
+        process_transform(&mut out, transform, true, &mut cx)?;
+        Ok((out, cx.trace.unwrap_or_default()))
+    }
+
+    /// Apply `transform` and also return the operations that changed nothing, such as
+    /// selectors matching no node and default keys that already held their value.
+    pub fn apply_with_unused(
+        &self,
+        source: &Value,
+        transform: &Value,
+    ) -> Result<(Value, Vec<TraceEvent>), JdtError> {
+        let (out, trace) = self.apply_traced(source, transform)?;
+        Ok((out, trace.into_iter().filter(|e| !e.changed).collect()))
+    }
+
+    /// Apply `transforms` in order to a single copy of `source`.
+    ///
+    /// Errors are wrapped in [`JdtError::Layer`] with the zero-based index of the
+    /// failing transform.
+    pub fn apply_all(&self, source: &Value, transforms: &[Value]) -> Result<Value, JdtError> {
+        let mut out = source.clone();
+        let mut cx = Context::new(self);
+        for (index, transform) in transforms.iter().enumerate() {
+            self.resolve(transform)
+                .and_then(|transform| process_transform(&mut out, &transform, true, &mut cx))
+                .map_err(|e| JdtError::Layer {
+                    index,
+                    source: Box::new(e),
+                })?;
+        }
+        Ok(out)
+    }
+}
 
 pub fn apply(source: &Value, transform: &Value) -> Result<Value, JdtError> {
-    let mut out = source.clone();
-    process_transform(&mut out, transform, true)?;
-    Ok(out)
+    ApplyOptions::default().apply(source, transform)
+}
+
+/// Apply `transform` to `document` in place; on error `document` is left unchanged.
+pub fn apply_in_place(document: &mut Value, transform: &Value) -> Result<(), JdtError> {
+    ApplyOptions::default().apply_in_place(document, transform)
+}
+
+/// Apply a stack of transforms in order, e.g. base, environment, then region overrides.
+pub fn apply_all(source: &Value, transforms: &[Value]) -> Result<Value, JdtError> {
+    ApplyOptions::default().apply_all(source, transforms)
+}
+
+/// Apply `transform`, returning the result and the JSON Patch of every change made.
+pub fn apply_with_patch(source: &Value, transform: &Value) -> Result<(Value, Value), JdtError> {
+    ApplyOptions::default().apply_with_patch(source, transform)
+}
+
+/// Apply `transform` to JSON text, rewriting only the parts it changes.
+pub fn apply_text(source: &str, transform: &Value) -> Result<String, JdtError> {
+    ApplyOptions::default().apply_text(source, transform)
+}
+
+/// Apply `transform` to every document of a YAML stream.
+#[cfg(feature = "yaml")]
+pub fn apply_yaml(source: &str, transform: &Value) -> Result<String, JdtError> {
+    ApplyOptions::default().apply_yaml(source, transform)
+}
+
+/// Apply `transform` to a TOML document.
+#[cfg(feature = "toml")]
+pub fn apply_toml(source: &str, transform: &Value) -> Result<String, JdtError> {
+    ApplyOptions::default().apply_toml(source, transform)
+}
+
+/// Apply `transform`, returning the result and a JSON Patch that undoes it; see
+/// [`apply_json_patch`](crate::apply_json_patch).
+pub fn apply_with_inverse(source: &Value, transform: &Value) -> Result<(Value, Value), JdtError> {
+    ApplyOptions::default().apply_with_inverse(source, transform)
+}
+
+/// Apply `transform`, returning the result and a log of which verb touched which node.
+pub fn apply_traced(
+    source: &Value,
+    transform: &Value,
+) -> Result<(Value, Vec<TraceEvent>), JdtError> {
+    ApplyOptions::default().apply_traced(source, transform)
+}
+
+/// Apply `transform`, returning the result and the verbs, selectors and default keys
+/// that had no effect on `source`.
+pub fn apply_with_unused(
+    source: &Value,
+    transform: &Value,
+) -> Result<(Value, Vec<TraceEvent>), JdtError> {
+    ApplyOptions::default().apply_with_unused(source, transform)
+}
+
+/// State threaded through a single application.
+struct Context<'a> {
+    opts: &'a ApplyOptions,
+    /// Absolute path of the node the current function works on.
+    path: Vec<PathItem>,
+    /// Every mutation so far, when requested.
+    edits: Option<Vec<Edit>>,
+    /// Whether the default transformation currently uses merge patch semantics.
+    merge_patch: bool,
+    /// Every operation so far, when requested.
+    trace: Option<Vec<TraceEvent>>,
+    /// Location in the transform of the value being applied, when tracing.
+    location: Vec<PathItem>,
+    /// Number of mutations so far; replacements by an equal value count only when tracing.
+    changes: usize,
+}
+
+impl<'a> Context<'a> {
+    fn new(opts: &'a ApplyOptions) -> Self {
+        Self {
+            opts,
+            path: Vec::new(),
+            edits: None,
+            merge_patch: opts.merge_patch,
+            trace: None,
+            location: Vec::new(),
+            changes: 0,
+        }
+    }
+
+    fn recording(opts: &'a ApplyOptions) -> Self {
+        Self {
+            edits: Some(Vec::new()),
+            ..Self::new(opts)
+        }
+    }
+
+    fn tracing(opts: &'a ApplyOptions) -> Self {
+        Self {
+            trace: Some(Vec::new()),
+            ..Self::new(opts)
+        }
+    }
+
+    fn into_edits(self) -> Vec<Edit> {
+        self.edits.unwrap_or_default()
+    }
+
+    /// The key of `obj` that the transform name `name` refers to.
+    fn key_of(&self, obj: &serde_json::Map<String, Value>, name: &str) -> Option<String> {
+        find_key(obj, name, self.opts.case_insensitive).cloned()
+    }
+
+    /// Whether mutations are recorded or traced, so callers must report them.
+    fn observing(&self) -> bool {
+        self.edits.is_some() || self.trace.is_some()
+    }
+
+    /// Run `f` with the current node moved to `rel` below it.
+    fn descend<R>(&mut self, rel: &[PathItem], f: impl FnOnce(&mut Self) -> R) -> R {
+        let depth = self.path.len();
+        self.path.extend_from_slice(rel);
+        let out = f(self);
+        self.path.truncate(depth);
+        out
+    }
+
+    /// Run `f` with the transform location moved to `rel` below it, then mark whether
+    /// the event logged there changed anything.
+    fn within<R>(&mut self, rel: &[PathItem], f: impl FnOnce(&mut Self) -> R) -> R {
+        let Some(start) = self.trace.as_ref().map(Vec::len) else {
+            return f(self);
+        };
+        let (depth, changes) = (self.location.len(), self.changes);
+        self.location.extend_from_slice(rel);
+        let out = f(self);
+        let changed = self.changes > changes;
+        if let Some(event) = self.trace.as_mut().and_then(|t| t.get_mut(start)) {
+            if event.location == patch::pointer(&self.location) {
+                event.changed = changed;
+            }
+        }
+        self.location.truncate(depth);
+        out
+    }
+
+    /// Log `verb` at the current transform location targeting `matched` below `source`,
+    /// the current node. Call before mutating.
+    fn traced(
+        &mut self,
+        verb: &'static str,
+        selector: Option<&str>,
+        source: &Value,
+        matched: &[Vec<PathItem>],
+        new: Option<&Value>,
+    ) {
+        let Some(events) = self.trace.as_mut() else {
+            return;
+        };
+        events.push(TraceEvent {
+            verb,
+            location: patch::pointer(&self.location),
+            selector: selector.map(str::to_string),
+            matched: matched
+                .iter()
+                .map(|rel| patch::pointer(&[self.path.as_slice(), rel].concat()))
+                .collect(),
+            old: matched
+                .iter()
+                .map(|rel| source.pointer(&patch::pointer(rel)).map(trace::summarize))
+                .collect(),
+            new: new.cloned(),
+            changed: false,
+        });
+    }
+
+    /// Run `f` with the default transformation's plain JDT semantics.
+    fn without_merge_patch<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let saved = std::mem::replace(&mut self.merge_patch, false);
+        let out = f(self);
+        self.merge_patch = saved;
+        out
+    }
+
+    fn record(&mut self, edit: impl FnOnce(Vec<PathItem>) -> Edit, rel: &[PathItem]) {
+        if let Some(edits) = self.edits.as_mut() {
+            let path = self.path.iter().chain(rel).cloned().collect();
+            edits.push(edit(path));
+        }
+    }
+
+    fn added(&mut self, rel: &[PathItem], value: &Value) {
+        self.changes += 1;
+        self.record(
+            |path| Edit::Add {
+                path,
+                value: value.clone(),
+            },
+            rel,
+        );
+    }
+
+    /// Record the removal of `old`, which was at `position` among its siblings.
+    fn removed(&mut self, rel: &[PathItem], position: usize, old: Value) {
+        self.changes += 1;
+        self.record(
+            |path| Edit::Remove {
+                path,
+                old,
+                position,
+            },
+            rel,
+        );
+    }
+
+    fn replaced(&mut self, rel: &[PathItem], old: Value, value: &Value) {
+        if self.trace.is_none() || old != *value {
+            self.changes += 1;
+        }
+        if old == *value {
+            // Setting a node to the value it has is not an edit.
+            return;
+        }
+        self.record(
+            |path| Edit::Replace {
+                path,
+                old,
+                value: value.clone(),
+            },
+            rel,
+        );
+    }
+
+    fn moved(&mut self, from: &[PathItem], to: &[PathItem], overwritten: Option<(usize, Value)>) {
+        self.changes += 1;
+        if self.edits.is_some() {
+            let from = self.path.iter().chain(from).cloned().collect();
+            self.record(
+                |path| Edit::Move {
+                    from,
+                    path,
+                    overwritten,
+                },
+                to,
+            );
+        }
+    }
 }
 
-fn process_transform(source: &mut Value, transform: &Value, is_root: bool) -> Result<(), JdtError> {
+fn process_transform(
+    source: &mut Value,
+    transform: &Value,
+    is_root: bool,
+    cx: &mut Context,
+) -> Result<(), JdtError> {
     let Some(transform_obj) = transform.as_object() else {
         return Err(JdtError::TransformNotObject);
     };
+    // An array or a scalar has no keys to merge into, so only verbs apply to it.
+    if !source.is_object() {
+        if transform_obj.keys().any(|k| !is_jdt_syntax(k)) {
+            return Err(JdtError::SourceNotObject);
+        }
+        apply_verbs(source, transform_obj, is_root, cx)?;
+        return Ok(());
+    }
     let Some(source_obj) = source.as_object_mut() else {
         return Err(JdtError::SourceNotObject);
     };
@@ -50,49 +588,71 @@ fn process_transform(source: &mut Value, transform: &Value, is_root: bool) -> Re
         if is_jdt_syntax(k) {
             continue;
         }
-        if matches!(v, Value::Object(_)) {
-            if let Some(child_src) = source_obj.get_mut(k) {
-                if child_src.is_object() {
-                    process_transform(child_src, v, false)?;
-                    recursed.insert(k.clone());
-                }
+        if matches!(v, Value::Object(_)) && !is_array_merge_call(v) {
+            let Some(key) = cx.key_of(source_obj, k) else {
+                continue;
+            };
+            if let Some(child_src) = source_obj.get_mut(&key).filter(|c| c.is_object()) {
+                let rel = [PathItem::Key(key)];
+                let location = [PathItem::Key(k.clone())];
+                cx.descend(&rel, |cx| {
+                    cx.within(&location, |cx| process_transform(child_src, v, false, cx))
+                })?;
+                recursed.insert(k.clone());
             }
         }
     }
 
     // 2) Verbs (Remove, Replace, Rename, Merge) following the Microsoft processor chain.
+    if apply_verbs(source, transform_obj, is_root, cx)? == Control::Halt {
+        return Ok(());
+    }
+
+    // 3) Default transformation: merge non-@jdt keys (except those already recursed).
+    default_transform(source, transform_obj, &recursed, cx)?;
+
+    Ok(())
+}
+
+/// Run the verbs of `transform_obj` on `source`, stopping at one that replaced it.
+fn apply_verbs(
+    source: &mut Value,
+    transform_obj: &serde_json::Map<String, Value>,
+    is_root: bool,
+    cx: &mut Context,
+) -> Result<Control, JdtError> {
     if let Some(v) = transform_obj.get(VERB_REMOVE) {
-        let control = verb_remove(source, v, is_root)?;
+        let rel = [PathItem::Key(VERB_REMOVE.to_string())];
+        let control = cx.within(&rel, |cx| verb_remove(source, v, is_root, cx))?;
         if control == Control::Halt {
-            return Ok(());
+            return Ok(Control::Halt);
         }
     }
 
     if let Some(v) = transform_obj.get(VERB_REPLACE) {
-        let control = verb_replace(source, v, is_root)?;
+        let rel = [PathItem::Key(VERB_REPLACE.to_string())];
+        let control = cx.within(&rel, |cx| verb_replace(source, v, is_root, cx))?;
         if control == Control::Halt {
-            return Ok(());
+            return Ok(Control::Halt);
         }
     }
 
     if let Some(v) = transform_obj.get(VERB_RENAME) {
-        let control = verb_rename(source, v, is_root)?;
+        let rel = [PathItem::Key(VERB_RENAME.to_string())];
+        let control = cx.within(&rel, |cx| verb_rename(source, v, is_root, cx))?;
         if control == Control::Halt {
-            return Ok(());
+            return Ok(Control::Halt);
         }
     }
 
     if let Some(v) = transform_obj.get(VERB_MERGE) {
-        let control = verb_merge(source, v, is_root)?;
+        let rel = [PathItem::Key(VERB_MERGE.to_string())];
+        let control = cx.within(&rel, |cx| verb_merge(source, v, is_root, cx))?;
         if control == Control::Halt {
-            return Ok(());
+            return Ok(Control::Halt);
         }
     }
-
-    // 3) Default transformation: merge non-@jdt keys (except those already recursed).
-    default_transform(source, transform_obj, &recursed);
-
-    Ok(())
+    Ok(Control::Continue)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -105,66 +665,388 @@ fn default_transform(
     source: &mut Value,
     transform_obj: &serde_json::Map<String, Value>,
     recursed: &std::collections::BTreeSet<String>,
-) {
-    let Some(source_obj) = source.as_object_mut() else {
-        return;
-    };
-
+    cx: &mut Context,
+) -> Result<(), JdtError> {
     for (k, v) in transform_obj.iter().filter(|(k, _)| !is_jdt_syntax(k)) {
         if recursed.contains(k.as_str()) {
             continue;
         }
+        cx.within(&[PathItem::Key(k.clone())], |cx| {
+            default_key(source, k, v, cx)
+        })?;
+    }
+    Ok(())
+}
 
-        match source_obj.get_mut(k) {
-            Some(existing) => {
-                if let (Some(dst), Some(src_arr)) = (existing.as_array_mut(), v.as_array()) {
-                    dst.extend(src_arr.iter().cloned());
-                } else {
-                    *existing = v.clone();
+/// Merge the transform value `v` into key `k` of `source`.
+fn default_key(source: &mut Value, k: &str, v: &Value, cx: &mut Context) -> Result<(), JdtError> {
+    let key = source
+        .as_object()
+        .and_then(|obj| cx.key_of(obj, k))
+        .unwrap_or_else(|| k.to_string());
+    let rel = [PathItem::Key(key.clone())];
+
+    // `{ "@jdt.arrayMerge": ..., "@jdt.value": [...] }` carries its own array policy.
+    let (v, strategy, location) = match v.as_object() {
+        Some(o) if is_array_merge_call(v) => (
+            o.get(ATTR_VALUE)
+                .ok_or(JdtError::MissingAttribute(ATTR_VALUE))?,
+            parse_array_merge(o)?,
+            vec![PathItem::Key(ATTR_VALUE.to_string())],
+        ),
+        _ => (v, None, Vec::new()),
+    };
+
+    cx.traced("default", None, source, &[rel.to_vec()], Some(v));
+    let Some(source_obj) = source.as_object_mut() else {
+        return Ok(());
+    };
+    if cx.merge_patch && v.is_null() {
+        if let Some((position, old)) = order::remove_key(source_obj, &key) {
+            cx.removed(&rel, position, old);
+        }
+        return Ok(());
+    }
+    // A merge patch object sets its non-null members where there is no object to merge into.
+    let stripped;
+    let v = if cx.merge_patch && v.is_object() {
+        stripped = patch::strip_nulls(v);
+        &stripped
+    } else {
+        v
+    };
+    match source_obj.get_mut(&key) {
+        Some(existing) => {
+            let merged = match existing {
+                Value::Array(dst) => {
+                    let opts = cx.opts;
+                    let fallback = if cx.merge_patch {
+                        &ArrayMerge::Replace
+                    } else {
+                        &opts.array_merge
+                    };
+                    let strategy = strategy.as_ref().unwrap_or(fallback);
+                    cx.descend(&rel, |cx| {
+                        cx.within(&location, |cx| merge_into_array(dst, v, strategy, cx))
+                    })?
                 }
+                // Indices merge as names into an object, such as one keyed by port number.
+                Value::Object(_) => match positional_members(v, strategy.as_ref()) {
+                    Some(members) => {
+                        cx.descend(&rel, |cx| {
+                            cx.within(&location, |cx| {
+                                process_transform(existing, &members, false, cx)
+                            })
+                        })?;
+                        true
+                    }
+                    None => false,
+                },
+                _ => false,
+            };
+            if !merged {
+                let v = array_value(v, strategy.as_ref());
+                let old = std::mem::replace(existing, v.clone());
+                cx.replaced(&rel, old, &v);
+            }
+        }
+        None => {
+            let v = array_value(v, strategy.as_ref());
+            cx.added(&rel, &v);
+            source_obj.insert(key, v);
+        }
+    }
+    Ok(())
+}
+
+/// `value` with every nested `{ "@jdt.arrayMerge": ..., "@jdt.value": [...] }` replaced by
+/// its array, as written where a transform value replaces or adds to the source.
+fn array_merge_values(value: &Value) -> Value {
+    match value {
+        Value::Object(o) if is_array_merge_call(value) && o.contains_key(ATTR_VALUE) => {
+            array_value(&o[ATTR_VALUE], parse_array_merge(o).ok().flatten().as_ref())
+        }
+        Value::Object(o) => Value::Object(
+            o.iter()
+                .map(|(k, v)| (k.clone(), array_merge_values(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(array_merge_values).collect()),
+        other => other.clone(),
+    }
+}
+
+/// What the `@jdt.value` `v` of a call merging with `strategy` writes where there is no
+/// array to merge into: elements keyed by indices `0..n` become an array, and elements
+/// with gaps between their indices stay an object, so that no index shifts.
+fn array_value(v: &Value, strategy: Option<&ArrayMerge>) -> Value {
+    let indexed = v
+        .as_object()
+        .filter(|_| strategy == Some(&ArrayMerge::Positional))
+        .and_then(indexed_elements)
+        .filter(|elements| elements.iter().enumerate().all(|(i, &(j, _, _))| i == j));
+    match indexed {
+        Some(elements) => Value::Array(
+            elements
+                .into_iter()
+                .map(|(_, _, el)| array_merge_values(el))
+                .collect(),
+        ),
+        None => array_merge_values(v),
+    }
+}
+
+fn merge_arrays(
+    dst: &mut Vec<Value>,
+    src: &[Value],
+    strategy: &ArrayMerge,
+    cx: &mut Context,
+) -> Result<(), JdtError> {
+    match strategy {
+        ArrayMerge::Append => {
+            for el in src {
+                push_element(dst, el, cx);
+            }
+        }
+        ArrayMerge::Replace => {
+            let old = std::mem::replace(dst, src.iter().map(array_merge_values).collect());
+            if cx.observing() {
+                cx.replaced(&[], Value::Array(old), &Value::Array(dst.clone()));
+            }
+        }
+        ArrayMerge::Union => {
+            for el in src {
+                if !dst.contains(el) {
+                    push_element(dst, el, cx);
+                }
+            }
+        }
+        ArrayMerge::Positional => {
+            let elements = src.iter().enumerate();
+            merge_positions(dst, elements.map(|(i, el)| (i, PathItem::Index(i), el)), cx)?;
+        }
+        ArrayMerge::Keyed {
+            key,
+            remove_unmatched,
+        } => {
+            let ignore_case = cx.opts.case_insensitive;
+            let mut matched = vec![false; dst.len()];
+            for (j, el) in src.iter().enumerate() {
+                let id = merge_key_of(el, key, ignore_case);
+                let mut found = false;
+                if let Some(id) = id {
+                    for (i, existing) in dst.iter_mut().enumerate() {
+                        if merge_key_of(existing, key, ignore_case) == Some(id) {
+                            cx.descend(&[PathItem::Index(i)], |cx| {
+                                cx.within(&[PathItem::Index(j)], |cx| {
+                                    process_transform(existing, el, false, cx)
+                                })
+                            })?;
+                            matched[i] = true;
+                            found = true;
+                        }
+                    }
+                }
+                if !found {
+                    if el
+                        .as_object()
+                        .is_some_and(|o| o.keys().any(|k| is_jdt_syntax(k)))
+                    {
+                        // Verbs in a new element run as they would on a match.
+                        let i = dst.len();
+                        push_element(dst, &Value::Object(serde_json::Map::new()), cx);
+                        cx.descend(&[PathItem::Index(i)], |cx| {
+                            cx.within(&[PathItem::Index(j)], |cx| {
+                                process_transform(&mut dst[i], el, false, cx)
+                            })
+                        })?;
+                    } else {
+                        push_element(dst, el, cx);
+                    }
+                    matched.push(true);
+                }
+            }
+            if *remove_unmatched {
+                for i in (0..dst.len()).rev() {
+                    if !matched[i] {
+                        let old = dst.remove(i);
+                        cx.removed(&[PathItem::Index(i)], i, old);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The identity field `key` of a keyed-merge element.
+fn merge_key_of<'a>(el: &'a Value, key: &str, ignore_case: bool) -> Option<&'a Value> {
+    let obj = el.as_object()?;
+    obj.get(find_key(obj, key, ignore_case)?)
+}
+
+/// Merge each element into the element of `dst` at its index, appending those past the
+/// end at their index after `null` padding; the path item locates the element in the
+/// transform.
+fn merge_positions<'v>(
+    dst: &mut Vec<Value>,
+    elements: impl IntoIterator<Item = (usize, PathItem, &'v Value)>,
+    cx: &mut Context,
+) -> Result<(), JdtError> {
+    for (i, location, el) in elements {
+        match dst.get_mut(i) {
+            Some(existing) => {
+                let opts = cx.opts;
+                cx.descend(&[PathItem::Index(i)], |cx| {
+                    cx.within(&[location], |cx| {
+                        merge_into_value(existing, el, &opts.array_merge, false, cx)
+                    })
+                })?
             }
             None => {
-                source_obj.insert(k.clone(), v.clone());
+                while dst.len() < i {
+                    push_element(dst, &Value::Null, cx);
+                }
+                push_element(dst, el, cx);
             }
         }
     }
+    Ok(())
+}
+
+/// The elements of a positional `@jdt.value` given as an object keyed by index, such as
+/// `{"1": "x"}`, in index order; `None` for any other object.
+fn indexed_elements(obj: &serde_json::Map<String, Value>) -> Option<Vec<(usize, &String, &Value)>> {
+    let mut elements = obj
+        .iter()
+        .map(|(k, v)| Some((array_index(k)?, k, v)))
+        .collect::<Option<Vec<_>>>()
+        .filter(|elements| !elements.is_empty())?;
+    elements.sort_unstable_by_key(|&(i, _, _)| i);
+    Some(elements)
+}
+
+/// The `@jdt.value` `v` of a positional merge as an object keyed by index, to be merged by
+/// name into a source object; `None` for other policies and values.
+fn positional_members<'v>(v: &'v Value, strategy: Option<&ArrayMerge>) -> Option<Cow<'v, Value>> {
+    if strategy != Some(&ArrayMerge::Positional) {
+        return None;
+    }
+    match v {
+        Value::Array(items) if !items.is_empty() => Some(Cow::Owned(Value::Object(
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, el)| (i.to_string(), el.clone()))
+                .collect(),
+        ))),
+        Value::Object(obj) => indexed_elements(obj).map(|_| Cow::Borrowed(v)),
+        _ => None,
+    }
+}
+
+/// The index a name such as `"2"` stands for; names with leading zeros are not indices.
+pub(crate) fn array_index(name: &str) -> Option<usize> {
+    let digits = !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit());
+    if digits && (name == "0" || !name.starts_with('0')) {
+        name.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Merge the `@jdt.value` `v` into the array `dst`: an array with `strategy`, or, for a
+/// positional merge, an object of elements keyed by index. Returns `false` without
+/// changing anything for other values.
+fn merge_into_array(
+    dst: &mut Vec<Value>,
+    v: &Value,
+    strategy: &ArrayMerge,
+    cx: &mut Context,
+) -> Result<bool, JdtError> {
+    match v {
+        Value::Array(src) => merge_arrays(dst, src, strategy, cx)?,
+        Value::Object(obj) if *strategy == ArrayMerge::Positional => {
+            let Some(elements) = indexed_elements(obj) else {
+                return Ok(false);
+            };
+            let elements = elements.into_iter();
+            merge_positions(
+                dst,
+                elements.map(|(i, k, el)| (i, PathItem::Key(k.clone()), el)),
+                cx,
+            )?;
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
 }
 
-fn verb_remove(source: &mut Value, value: &Value, is_root: bool) -> Result<Control, JdtError> {
+fn push_element(dst: &mut Vec<Value>, el: &Value, cx: &mut Context) {
+    let el = array_merge_values(el);
+    cx.added(&[PathItem::Index(dst.len())], &el);
+    dst.push(el);
+}
+
+fn verb_remove(
+    source: &mut Value,
+    value: &Value,
+    is_root: bool,
+    cx: &mut Context,
+) -> Result<Control, JdtError> {
     if let Some(arr) = value.as_array() {
-        for el in arr {
-            if verb_remove_core(source, el, is_root)? == Control::Halt {
+        for (i, el) in arr.iter().enumerate() {
+            let control = cx.within(&[PathItem::Index(i)], |cx| {
+                verb_remove_core(source, el, is_root, cx)
+            })?;
+            if control == Control::Halt {
                 return Ok(Control::Halt);
             }
         }
         return Ok(Control::Continue);
     }
-    verb_remove_core(source, value, is_root)
+    verb_remove_core(source, value, is_root, cx)
 }
 
-fn verb_remove_core(source: &mut Value, value: &Value, is_root: bool) -> Result<Control, JdtError> {
+fn verb_remove_core(
+    source: &mut Value,
+    value: &Value,
+    is_root: bool,
+    cx: &mut Context,
+) -> Result<Control, JdtError> {
     match value {
         Value::String(name) => {
+            let key = source
+                .as_object()
+                .and_then(|obj| cx.key_of(obj, name))
+                .unwrap_or_else(|| name.clone());
+            let rel = vec![PathItem::Key(key.clone())];
+            cx.traced("remove", None, source, &[rel], None);
             let Some(obj) = source.as_object_mut() else {
                 return Err(JdtError::SourceNotObject);
             };
-            obj.remove(name);
+            if let Some((position, old)) = order::remove_key(obj, &key) {
+                cx.removed(&[PathItem::Key(key)], position, old);
+            }
             Ok(Control::Continue)
         }
         Value::Bool(b) => {
+            let matched = if *b { vec![Vec::new()] } else { Vec::new() };
+            cx.traced("remove", None, source, &matched, None);
             if *b {
                 if is_root {
                     return Err(JdtError::RootOperationNotAllowed);
                 }
-                *source = Value::Null;
+                let old = std::mem::replace(source, Value::Null);
+                cx.replaced(&[], old, &Value::Null);
                 return Ok(Control::Halt);
             }
             Ok(Control::Continue)
         }
         Value::Object(o) => {
-            let selector = parse_selector_required(o)?;
-            let paths = selector.select_paths(source);
-            remove_paths(source, &paths, is_root)?;
+            let paths = select(o, source, cx)?;
+            cx.traced("remove", selector_str(o), source, &paths, None);
+            remove_paths(source, &paths, is_root, cx)?;
             Ok(Control::Continue)
         }
         Value::Null | Value::Number(_) | Value::Array(_) => Err(JdtError::TransformNotObject),
@@ -175,6 +1057,7 @@ fn remove_paths(
     source: &mut Value,
     paths: &[Vec<PathItem>],
     is_root: bool,
+    cx: &mut Context,
 ) -> Result<(), JdtError> {
     let mut paths = paths.to_vec();
     // Remove deep paths first; for array elements, descending indices.
@@ -185,65 +1068,87 @@ fn remove_paths(
             if is_root {
                 return Err(JdtError::RootOperationNotAllowed);
             }
-            *source = Value::Null;
+            let old = std::mem::replace(source, Value::Null);
+            cx.replaced(&[], old, &Value::Null);
             continue;
         }
         let Some((last, parent_path)) = path.split_last() else {
             continue;
         };
         if let Some(parent) = get_mut_at(source, parent_path) {
-            match (parent, last) {
-                (Value::Object(obj), PathItem::Key(k)) => {
-                    obj.remove(k);
-                }
-                (Value::Array(arr), PathItem::Index(i)) => {
-                    if *i < arr.len() {
-                        arr.remove(*i);
-                    }
+            let old = match (parent, last) {
+                (Value::Object(obj), PathItem::Key(k)) => order::remove_key(obj, k),
+                (Value::Array(arr), PathItem::Index(i)) if *i < arr.len() => {
+                    Some((*i, arr.remove(*i)))
                 }
-                _ => {}
+                _ => None,
+            };
+            if let Some((position, old)) = old {
+                cx.removed(&path, position, old);
             }
         }
     }
     Ok(())
 }
 
-fn verb_replace(source: &mut Value, value: &Value, is_root: bool) -> Result<Control, JdtError> {
+fn verb_replace(
+    source: &mut Value,
+    value: &Value,
+    is_root: bool,
+    cx: &mut Context,
+) -> Result<Control, JdtError> {
     if let Some(arr) = value.as_array() {
-        for el in arr {
-            if verb_replace_core(source, el, is_root)? == Control::Halt {
+        for (i, el) in arr.iter().enumerate() {
+            let control = cx.within(&[PathItem::Index(i)], |cx| {
+                verb_replace_core(source, el, is_root, cx)
+            })?;
+            if control == Control::Halt {
                 return Ok(Control::Halt);
             }
         }
         return Ok(Control::Continue);
     }
-    verb_replace_core(source, value, is_root)
+    verb_replace_core(source, value, is_root, cx)
 }
 
 fn verb_replace_core(
     source: &mut Value,
     value: &Value,
     is_root: bool,
+    cx: &mut Context,
 ) -> Result<Control, JdtError> {
     match value {
         Value::Object(o) => {
             if is_attributed_call(o) {
-                let selector = parse_selector_required(o)?;
                 let replacement = o
                     .get(ATTR_VALUE)
                     .ok_or(JdtError::MissingAttribute(ATTR_VALUE))?;
-                apply_replace_selector(source, &selector, replacement, is_root)
+                let paths = select(o, source, cx)?;
+                cx.traced(
+                    "replace",
+                    selector_str(o),
+                    source,
+                    &paths,
+                    Some(replacement),
+                );
+                apply_replace_selector(source, paths, replacement, is_root, cx)
             } else {
                 // Replace current object with given object (root allowed).
-                *source = Value::Object(o.clone());
+                cx.traced("replace", None, source, &[Vec::new()], Some(value));
+                let value = array_merge_values(value);
+                let old = std::mem::replace(source, value.clone());
+                cx.replaced(&[], old, &value);
                 Ok(Control::Halt)
             }
         }
         _ => {
-            if is_root {
+            cx.traced("replace", None, source, &[Vec::new()], Some(value));
+            if is_root && source.is_object() {
                 return Err(JdtError::RootOperationNotAllowed);
             }
-            *source = value.clone();
+            let value = array_merge_values(value);
+            let old = std::mem::replace(source, value.clone());
+            cx.replaced(&[], old, &value);
             Ok(Control::Halt)
         }
     }
@@ -251,17 +1156,19 @@ fn verb_replace_core(
 
 fn apply_replace_selector(
     source: &mut Value,
-    selector: &JsonPath,
+    paths: Vec<Vec<PathItem>>,
     replacement: &Value,
     is_root: bool,
+    cx: &mut Context,
 ) -> Result<Control, JdtError> {
-    let paths = selector.select_paths(source);
+    let replacement = &array_merge_values(replacement);
     for path in paths {
         if path.is_empty() {
-            if is_root && !replacement.is_object() {
+            if is_root && source.is_object() && !replacement.is_object() {
                 return Err(JdtError::RootOperationNotAllowed);
             }
-            *source = replacement.clone();
+            let old = std::mem::replace(source, replacement.clone());
+            cx.replaced(&[], old, replacement);
             return Ok(Control::Halt);
         }
         let Some((last, parent_path)) = path.split_last() else {
@@ -272,66 +1179,97 @@ fn apply_replace_selector(
         };
         match (parent, last) {
             (Value::Object(obj), PathItem::Key(k)) => {
-                obj.insert(k.clone(), replacement.clone());
-            }
-            (Value::Array(arr), PathItem::Index(i)) => {
-                if *i < arr.len() {
-                    arr[*i] = replacement.clone();
+                match obj.insert(k.clone(), replacement.clone()) {
+                    Some(old) => cx.replaced(&path, old, replacement),
+                    None => cx.added(&path, replacement),
                 }
             }
+            (Value::Array(arr), PathItem::Index(i)) if *i < arr.len() => {
+                let old = std::mem::replace(&mut arr[*i], replacement.clone());
+                cx.replaced(&path, old, replacement);
+            }
             _ => {}
         }
     }
     Ok(Control::Continue)
 }
 
-fn verb_rename(source: &mut Value, value: &Value, _is_root: bool) -> Result<Control, JdtError> {
+fn verb_rename(
+    source: &mut Value,
+    value: &Value,
+    _is_root: bool,
+    cx: &mut Context,
+) -> Result<Control, JdtError> {
     if let Some(arr) = value.as_array() {
-        for el in arr {
-            verb_rename_core(source, el)?;
+        for (i, el) in arr.iter().enumerate() {
+            cx.within(&[PathItem::Index(i)], |cx| verb_rename_core(source, el, cx))?;
         }
         return Ok(Control::Continue);
     }
-    verb_rename_core(source, value)?;
+    verb_rename_core(source, value, cx)?;
     Ok(Control::Continue)
 }
 
-fn verb_rename_core(source: &mut Value, value: &Value) -> Result<(), JdtError> {
+fn verb_rename_core(source: &mut Value, value: &Value, cx: &mut Context) -> Result<(), JdtError> {
     let Some(rename_obj) = value.as_object() else {
         return Err(JdtError::TransformNotObject);
     };
+    let collision = parse_rename_collision(rename_obj)?.unwrap_or(cx.opts.rename_collision);
 
     if is_attributed_call(rename_obj) {
-        let selector = parse_selector_required(rename_obj)?;
         let new_name = rename_obj
             .get(ATTR_VALUE)
             .ok_or(JdtError::MissingAttribute(ATTR_VALUE))?
             .as_str()
             .ok_or(JdtError::AttributeNotString(ATTR_VALUE))?
             .to_string();
-        let paths = selector.select_paths(source);
+        let paths = select(rename_obj, source, cx)?;
+        let new = Value::String(new_name.clone());
+        cx.traced(
+            "rename",
+            selector_str(rename_obj),
+            source,
+            &paths,
+            Some(&new),
+        );
         for path in paths {
-            rename_at_path(source, &path, &new_name)?;
+            rename_at_path(source, &path, &new_name, collision, cx)?;
         }
         return Ok(());
     }
 
     // Direct mapping form: { "A": "Astar", ... }
+    let matched: Vec<_> = rename_obj
+        .keys()
+        .filter(|k| *k != ATTR_ON_COLLISION)
+        .map(|k| {
+            let key = source.as_object().and_then(|obj| cx.key_of(obj, k));
+            vec![PathItem::Key(key.unwrap_or_else(|| k.clone()))]
+        })
+        .collect();
+    cx.traced("rename", None, source, &matched, Some(value));
     let Some(obj) = source.as_object_mut() else {
         return Err(JdtError::SourceNotObject);
     };
     for (old, newv) in rename_obj.iter() {
+        if old == ATTR_ON_COLLISION {
+            continue;
+        }
         let Some(new_name) = newv.as_str() else {
             return Err(JdtError::AttributeNotString(ATTR_VALUE));
         };
-        if let Some(val) = obj.remove(old) {
-            obj.insert(new_name.to_string(), val);
-        }
+        rename_key(obj, old, new_name, collision, cx)?;
     }
     Ok(())
 }
 
-fn rename_at_path(source: &mut Value, path: &[PathItem], new_name: &str) -> Result<(), JdtError> {
+fn rename_at_path(
+    source: &mut Value,
+    path: &[PathItem],
+    new_name: &str,
+    collision: RenameCollision,
+    cx: &mut Context,
+) -> Result<(), JdtError> {
     let Some((last, parent_path)) = path.split_last() else {
         return Err(JdtError::RenameNotProperty);
     };
@@ -339,50 +1277,149 @@ fn rename_at_path(source: &mut Value, path: &[PathItem], new_name: &str) -> Resu
         return Ok(());
     };
     match (parent, last) {
-        (Value::Object(obj), PathItem::Key(k)) => {
-            if let Some(val) = obj.remove(k) {
-                obj.insert(new_name.to_string(), val);
+        (Value::Object(obj), PathItem::Key(k)) => cx.descend(parent_path, |cx| {
+            rename_key(obj, k, new_name, collision, cx)
+        }),
+        _ => Err(JdtError::RenameNotProperty),
+    }
+}
+
+/// Rename `old` to `new_name` on `obj`, the current node of `cx`.
+fn rename_key(
+    obj: &mut serde_json::Map<String, Value>,
+    old: &str,
+    new_name: &str,
+    collision: RenameCollision,
+    cx: &mut Context,
+) -> Result<(), JdtError> {
+    let ignore_case = cx.opts.case_insensitive;
+    let Some(old) = cx.key_of(obj, old) else {
+        return Ok(());
+    };
+    if old == new_name {
+        return Ok(());
+    }
+    // Ignoring case, renaming a key to another spelling of itself is not a collision.
+    let existing = match obj.get_key_value(new_name) {
+        Some((key, _)) => Some(key.clone()),
+        None if ignore_case => obj
+            .keys()
+            .find(|key| **key != old && keys_equal_ignore_case(key, new_name))
+            .cloned(),
+        None => None,
+    };
+    let from = [PathItem::Key(old.clone())];
+    let to = [PathItem::Key(new_name.to_string())];
+    if let Some(existing) = existing {
+        match collision {
+            RenameCollision::Overwrite if existing != new_name => {
+                if let Some((position, value)) = order::remove_key(obj, &existing) {
+                    cx.removed(&[PathItem::Key(existing)], position, value);
+                }
+            }
+            RenameCollision::Overwrite => {}
+            RenameCollision::Error => return Err(JdtError::RenameCollision(new_name.to_string())),
+            RenameCollision::KeepExisting => return Ok(()),
+            RenameCollision::Merge => {
+                // The existing key keeps its place and spelling.
+                let to = [PathItem::Key(existing.clone())];
+                if let Some((position, val)) = order::remove_key(obj, &old) {
+                    if let Some(existing) = obj.get_mut(&existing) {
+                        let previous = cx.observing().then(|| (val.clone(), existing.clone()));
+                        deep_merge(existing, val, ignore_case);
+                        if let Some((renamed, previous)) = previous {
+                            cx.removed(&from, position, renamed);
+                            cx.replaced(&to, previous, existing);
+                        }
+                    }
+                }
+                return Ok(());
             }
-            Ok(())
         }
-        _ => Err(JdtError::RenameNotProperty),
+    }
+    let overwritten = order::rename_key(obj, &old, new_name.to_string());
+    cx.moved(&from, &to, overwritten);
+    Ok(())
+}
+
+/// Plain data merge used for renames: objects merge recursively, arrays append and any
+/// other `incoming` value wins.
+fn deep_merge(existing: &mut Value, incoming: Value, ignore_case: bool) {
+    match (existing, incoming) {
+        (Value::Object(dst), Value::Object(src)) => {
+            for (k, v) in src {
+                let k = find_key(dst, &k, ignore_case).cloned().unwrap_or(k);
+                match dst.get_mut(&k) {
+                    Some(d) => deep_merge(d, v, ignore_case),
+                    None => {
+                        dst.insert(k, v);
+                    }
+                }
+            }
+        }
+        (Value::Array(dst), Value::Array(src)) => dst.extend(src),
+        (existing, incoming) => *existing = incoming,
     }
 }
 
-fn verb_merge(source: &mut Value, value: &Value, is_root: bool) -> Result<Control, JdtError> {
+fn verb_merge(
+    source: &mut Value,
+    value: &Value,
+    is_root: bool,
+    cx: &mut Context,
+) -> Result<Control, JdtError> {
     if let Some(arr) = value.as_array() {
-        for el in arr {
-            verb_merge_core(source, el, is_root)?;
+        for (i, el) in arr.iter().enumerate() {
+            cx.within(&[PathItem::Index(i)], |cx| {
+                verb_merge_core(source, el, is_root, cx)
+            })?;
         }
         return Ok(Control::Continue);
     }
-    verb_merge_core(source, value, is_root)?;
+    verb_merge_core(source, value, is_root, cx)?;
     Ok(Control::Continue)
 }
 
-fn verb_merge_core(source: &mut Value, value: &Value, is_root: bool) -> Result<(), JdtError> {
+fn verb_merge_core(
+    source: &mut Value,
+    value: &Value,
+    is_root: bool,
+    cx: &mut Context,
+) -> Result<(), JdtError> {
     match value {
         Value::Object(o) => {
             if is_attributed_call(o) {
-                let selector = parse_selector_required(o)?;
                 let merge_value = o
                     .get(ATTR_VALUE)
                     .ok_or(JdtError::MissingAttribute(ATTR_VALUE))?;
-                let paths = selector.select_paths(source);
-                for path in paths {
-                    merge_at_path(source, &path, merge_value, is_root)?;
-                }
-                Ok(())
+                let strategy = parse_array_merge(o)?;
+                let opts = cx.opts;
+                let strategy = strategy.as_ref().unwrap_or(&opts.array_merge);
+                let paths = select(o, source, cx)?;
+                cx.traced("merge", selector_str(o), source, &paths, Some(merge_value));
+                let rel = [PathItem::Key(ATTR_VALUE.to_string())];
+                cx.within(&rel, |cx| {
+                    cx.without_merge_patch(|cx| {
+                        for path in paths {
+                            merge_at_path(source, &path, merge_value, strategy, is_root, cx)?;
+                        }
+                        Ok(())
+                    })
+                })
             } else {
                 // Merge without attributes: run a nested transform at this node.
-                process_transform(source, value, is_root)
+                cx.traced("merge", None, source, &[Vec::new()], Some(value));
+                cx.without_merge_patch(|cx| process_transform(source, value, is_root, cx))
             }
         }
         _ => {
-            if is_root {
+            cx.traced("merge", None, source, &[Vec::new()], Some(value));
+            if is_root && source.is_object() {
                 return Err(JdtError::RootOperationNotAllowed);
             }
-            *source = value.clone();
+            let value = array_merge_values(value);
+            let old = std::mem::replace(source, value.clone());
+            cx.replaced(&[], old, &value);
             Ok(())
         }
     }
@@ -392,53 +1429,70 @@ fn merge_at_path(
     source: &mut Value,
     path: &[PathItem],
     merge_value: &Value,
+    strategy: &ArrayMerge,
     is_root: bool,
+    cx: &mut Context,
 ) -> Result<(), JdtError> {
     let is_doc_root = is_root && path.is_empty();
     if path.is_empty() {
-        return merge_into_value(source, merge_value, is_doc_root);
+        return merge_into_value(source, merge_value, strategy, is_doc_root, cx);
     }
-    let Some((last, parent_path)) = path.split_last() else {
+    let Some(target) = get_mut_at(source, path) else {
         return Ok(());
     };
-    let Some(parent) = get_mut_at(source, parent_path) else {
-        return Ok(());
-    };
-    match (parent, last) {
-        (Value::Object(obj), PathItem::Key(k)) => {
-            let Some(target) = obj.get_mut(k) else {
-                return Ok(());
-            };
-            merge_into_value(target, merge_value, false)
-        }
-        (Value::Array(arr), PathItem::Index(i)) => {
-            if *i < arr.len() {
-                let target = &mut arr[*i];
-                merge_into_value(target, merge_value, false)?;
-            }
-            Ok(())
-        }
-        _ => Ok(()),
-    }
+    cx.descend(path, |cx| {
+        merge_into_value(target, merge_value, strategy, false, cx)
+    })
 }
 
+/// Merge `merge_value` into `target`, the current node of `cx`.
 fn merge_into_value(
     target: &mut Value,
     merge_value: &Value,
+    strategy: &ArrayMerge,
     is_root: bool,
+    cx: &mut Context,
 ) -> Result<(), JdtError> {
+    if let Some(call) = merge_value.as_object().filter(|_| target.is_object()) {
+        if is_array_merge_call(merge_value) {
+            let v = call
+                .get(ATTR_VALUE)
+                .ok_or(JdtError::MissingAttribute(ATTR_VALUE))?;
+            let strategy = parse_array_merge(call)?;
+            if let Some(members) = positional_members(v, strategy.as_ref()) {
+                let location = [PathItem::Key(ATTR_VALUE.to_string())];
+                return cx.within(&location, |cx| {
+                    process_transform(target, &members, is_root, cx)
+                });
+            }
+        }
+    }
     if target.is_object() && merge_value.is_object() {
-        process_transform(target, merge_value, is_root)?;
+        process_transform(target, merge_value, is_root, cx)?;
         return Ok(());
     }
     if let (Some(dst), Some(src)) = (target.as_array_mut(), merge_value.as_array()) {
-        dst.extend(src.iter().cloned());
-        return Ok(());
+        return merge_arrays(dst, src, strategy, cx);
+    }
+    // `{ "@jdt.arrayMerge": ..., "@jdt.value": ... }` carries its own array policy.
+    if let (Some(dst), Some(call)) = (target.as_array_mut(), merge_value.as_object()) {
+        if is_array_merge_call(merge_value) {
+            let v = call
+                .get(ATTR_VALUE)
+                .ok_or(JdtError::MissingAttribute(ATTR_VALUE))?;
+            let strategy = parse_array_merge(call)?.unwrap_or_else(|| strategy.clone());
+            let location = [PathItem::Key(ATTR_VALUE.to_string())];
+            if cx.within(&location, |cx| merge_into_array(dst, v, &strategy, cx))? {
+                return Ok(());
+            }
+        }
     }
-    if is_root {
+    if is_root && target.is_object() {
         return Err(JdtError::RootOperationNotAllowed);
     }
-    *target = merge_value.clone();
+    let merge_value = array_merge_values(merge_value);
+    let old = std::mem::replace(target, merge_value.clone());
+    cx.replaced(&[], old, &merge_value);
     Ok(())
 }
 
@@ -452,11 +1506,166 @@ fn parse_selector_required(obj: &serde_json::Map<String, Value>) -> Result<JsonP
     Ok(JsonPath::parse(path_str)?)
 }
 
-fn is_attributed_call(obj: &serde_json::Map<String, Value>) -> bool {
+/// Reads the array policy attributes of a node; `None` when the node sets neither.
+fn parse_array_merge(obj: &serde_json::Map<String, Value>) -> Result<Option<ArrayMerge>, JdtError> {
+    let policy = match obj.get(ATTR_ARRAY_MERGE) {
+        Some(v) => Some(
+            v.as_str()
+                .ok_or(JdtError::AttributeNotString(ATTR_ARRAY_MERGE))?,
+        ),
+        None => None,
+    };
+    let key = match obj.get(ATTR_MERGE_KEY) {
+        Some(v) => Some(
+            v.as_str()
+                .ok_or(JdtError::AttributeNotString(ATTR_MERGE_KEY))?,
+        ),
+        None => None,
+    };
+    let strategy = match (policy, key) {
+        (None, None) => return Ok(None),
+        (Some("keyed") | None, Some(key)) => {
+            let remove_unmatched = match obj.get(ATTR_REMOVE_UNMATCHED) {
+                Some(v) => v
+                    .as_bool()
+                    .ok_or(JdtError::AttributeNotBool(ATTR_REMOVE_UNMATCHED))?,
+                None => false,
+            };
+            ArrayMerge::Keyed {
+                key: key.to_string(),
+                remove_unmatched,
+            }
+        }
+        (Some("keyed"), None) => return Err(JdtError::MissingAttribute(ATTR_MERGE_KEY)),
+        (Some("append"), None) => ArrayMerge::Append,
+        (Some("replace"), None) => ArrayMerge::Replace,
+        (Some("union"), None) => ArrayMerge::Union,
+        (Some("positional"), None) => ArrayMerge::Positional,
+        (Some(other), _) => {
+            return Err(JdtError::InvalidAttributeValue(
+                ATTR_ARRAY_MERGE,
+                other.to_string(),
+            ))
+        }
+    };
+    Ok(Some(strategy))
+}
+
+fn parse_rename_collision(
+    obj: &serde_json::Map<String, Value>,
+) -> Result<Option<RenameCollision>, JdtError> {
+    let Some(v) = obj.get(ATTR_ON_COLLISION) else {
+        return Ok(None);
+    };
+    let policy = match v
+        .as_str()
+        .ok_or(JdtError::AttributeNotString(ATTR_ON_COLLISION))?
+    {
+        "overwrite" => RenameCollision::Overwrite,
+        "error" => RenameCollision::Error,
+        "keepExisting" => RenameCollision::KeepExisting,
+        "merge" => RenameCollision::Merge,
+        other => {
+            return Err(JdtError::InvalidAttributeValue(
+                ATTR_ON_COLLISION,
+                other.to_string(),
+            ))
+        }
+    };
+    Ok(Some(policy))
+}
+
+/// The nodes below `source` matched by the `@jdt.path` of `obj`, checked against its
+/// `@jdt.expect`; without one, no match is an error in strict mode.
+fn select(
+    obj: &serde_json::Map<String, Value>,
+    source: &Value,
+    cx: &Context,
+) -> Result<Vec<Vec<PathItem>>, JdtError> {
+    let selector = parse_selector_required(obj)?;
+    let expect = parse_expect(obj)?;
+    let paths = if cx.opts.case_insensitive {
+        selector.select_paths_ignore_case(source)
+    } else {
+        selector.select_paths(source)
+    };
+    let selector = || selector_str(obj).unwrap_or_default().to_string();
+    let actual = paths.len();
+    match expect {
+        Some((min, max)) if actual < min || max.is_some_and(|max| actual > max) => {
+            let expected = match max {
+                Some(max) if max == min => min.to_string(),
+                Some(max) => format!("{min}..{max}"),
+                None => format!("{min}.."),
+            };
+            return Err(JdtError::UnexpectedMatchCount {
+                selector: selector(),
+                expected,
+                actual,
+            });
+        }
+        None if actual == 0 && cx.opts.strict => return Err(JdtError::NoMatch(selector())),
+        _ => {}
+    }
+    Ok(paths)
+}
+
+/// Reads `@jdt.expect` as inclusive bounds: `n` for exactly `n` matches, or a
+/// `"min..max"` range where either end may be omitted.
+fn parse_expect(
+    obj: &serde_json::Map<String, Value>,
+) -> Result<Option<(usize, Option<usize>)>, JdtError> {
+    let Some(v) = obj.get(ATTR_EXPECT) else {
+        return Ok(None);
+    };
+    let invalid = || JdtError::InvalidAttributeValue(ATTR_EXPECT, v.to_string());
+    if let Some(n) = v.as_u64() {
+        let n = usize::try_from(n).map_err(|_| invalid())?;
+        return Ok(Some((n, Some(n))));
+    }
+    let text = v.as_str().ok_or_else(invalid)?.trim();
+    let bound = |s: &str| s.parse::<usize>().map_err(|_| invalid());
+    let (min, max) = match text.split_once("..") {
+        Some((min, max)) => {
+            let min = match min.trim() {
+                "" => 0,
+                min => bound(min)?,
+            };
+            let max = match max.trim() {
+                "" => None,
+                max => Some(bound(max)?),
+            };
+            (min, max)
+        }
+        None => {
+            let n = bound(text)?;
+            (n, Some(n))
+        }
+    };
+    if max.is_some_and(|max| max < min) {
+        return Err(invalid());
+    }
+    Ok(Some((min, max)))
+}
+
+/// The raw `@jdt.path` of an attributed call, for tracing.
+fn selector_str(obj: &serde_json::Map<String, Value>) -> Option<&str> {
+    obj.get(ATTR_PATH).and_then(Value::as_str)
+}
+
+pub(crate) fn is_attributed_call(obj: &serde_json::Map<String, Value>) -> bool {
     obj.contains_key(ATTR_PATH) || obj.contains_key(ATTR_VALUE)
 }
 
-fn is_jdt_syntax(key: &str) -> bool {
+/// A default-merge value of the form `{ "@jdt.arrayMerge": ..., "@jdt.value": [...] }`.
+pub(crate) fn is_array_merge_call(value: &Value) -> bool {
+    value.as_object().is_some_and(|o| {
+        (o.contains_key(ATTR_ARRAY_MERGE) || o.contains_key(ATTR_MERGE_KEY))
+            && !o.contains_key(ATTR_PATH)
+    })
+}
+
+pub(crate) fn is_jdt_syntax(key: &str) -> bool {
     matches!(key, VERB_REMOVE | VERB_REPLACE | VERB_RENAME | VERB_MERGE) || key.starts_with("@jdt.")
 }
 
@@ -0,0 +1,94 @@
+use crate::jsonpath::PathItem;
+use crate::patch;
+use crate::transform::JdtError;
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use serde_norway::Value as Yaml;
+
+/// Parse a YAML stream into one value per document.
+///
+/// Aliases are expanded and `<<` merge keys applied. Boolean and number keys become
+/// strings; null and collection keys, tags, and `.inf`/`.nan` are rejected.
+pub fn parse_yaml(text: &str) -> Result<Vec<Value>, JdtError> {
+    serde_norway::Deserializer::from_str(text)
+        .enumerate()
+        .map(|(index, document)| {
+            let mut yaml = Yaml::deserialize(document).map_err(|e| yaml_error(index, e))?;
+            yaml.apply_merge().map_err(|e| yaml_error(index, e))?;
+            let mut path = Vec::new();
+            to_json(yaml, &mut path).map_err(|what| {
+                let at = patch::pointer(&path);
+                JdtError::Yaml(format!("document {index}, at \"{at}\": {what}"))
+            })
+        })
+        .collect()
+}
+
+/// Render `documents` as a YAML stream, separating them with `---`. A null document
+/// is left empty.
+pub fn to_yaml(documents: &[Value]) -> Result<String, JdtError> {
+    let mut out = String::new();
+    for (index, document) in documents.iter().enumerate() {
+        if index > 0 {
+            out.push_str("---\n");
+        }
+        if !document.is_null() {
+            out += &serde_norway::to_string(document).map_err(|e| yaml_error(index, e))?;
+        }
+    }
+    Ok(out)
+}
+
+fn yaml_error(index: usize, e: serde_norway::Error) -> JdtError {
+    JdtError::Yaml(format!("document {index}: {e}"))
+}
+
+/// Convert `yaml`, leaving `path` at the offending node on error.
+fn to_json(yaml: Yaml, path: &mut Vec<PathItem>) -> Result<Value, String> {
+    Ok(match yaml {
+        Yaml::Null => Value::Null,
+        Yaml::Bool(b) => Value::Bool(b),
+        Yaml::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::from(i)
+            } else if let Some(u) = n.as_u64() {
+                Value::from(u)
+            } else {
+                let f = n.as_f64().expect("a YAML number is an integer or a float");
+                let n = Number::from_f64(f).ok_or_else(|| format!("{n} has no JSON equivalent"))?;
+                Value::Number(n)
+            }
+        }
+        Yaml::String(s) => Value::String(s),
+        Yaml::Sequence(items) => {
+            let mut array = Vec::with_capacity(items.len());
+            for (i, item) in items.into_iter().enumerate() {
+                path.push(PathItem::Index(i));
+                array.push(to_json(item, path)?);
+                path.pop();
+            }
+            Value::Array(array)
+        }
+        Yaml::Mapping(mapping) => {
+            let mut obj = Map::new();
+            for (key, value) in mapping {
+                let key = match key {
+                    Yaml::String(s) => s,
+                    Yaml::Bool(b) => b.to_string(),
+                    Yaml::Number(n) => n.to_string(),
+                    Yaml::Null => return Err("null keys are not supported".to_string()),
+                    _ => return Err("only scalar keys are supported".to_string()),
+                };
+                if obj.contains_key(&key) {
+                    return Err(format!("duplicate key {key:?}"));
+                }
+                path.push(PathItem::Key(key.clone()));
+                let value = to_json(value, path)?;
+                path.pop();
+                obj.insert(key, value);
+            }
+            Value::Object(obj)
+        }
+        Yaml::Tagged(tagged) => return Err(format!("tag {} is not supported", tagged.tag)),
+    })
+}
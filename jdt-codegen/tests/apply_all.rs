@@ -0,0 +1,70 @@
+use jdt_codegen::{apply, apply_all, ApplyOptions, ArrayMerge, JdtError};
+use serde_json::json;
+
+#[test]
+fn layers_apply_in_order() {
+    let source = json!({"Logging": {"Level": "Debug"}, "Port": 80});
+    let transforms = [
+        json!({"Logging": {"Level": "Warning"}, "Port": 8080}),
+        json!({"Port": 443, "Region": "eu-west"}),
+    ];
+    let result = apply_all(&source, &transforms).unwrap();
+    assert_eq!(
+        result,
+        json!({"Logging": {"Level": "Warning"}, "Port": 443, "Region": "eu-west"})
+    );
+}
+
+#[test]
+fn layers_match_repeated_apply() {
+    let source = json!({"a": [1], "b": {"c": 1}, "d": 1});
+    let t1 = json!({"a": [2], "@jdt.rename": {"d": "e"}});
+    let t2 = json!({"@jdt.remove": "b", "a": [3], "e": 2});
+    let sequential = apply(&apply(&source, &t1).unwrap(), &t2).unwrap();
+    assert_eq!(apply_all(&source, &[t1, t2]).unwrap(), sequential);
+}
+
+#[test]
+fn empty_stack_returns_source() {
+    let source = json!({"a": 1});
+    assert_eq!(apply_all(&source, &[]).unwrap(), source);
+}
+
+#[test]
+fn options_apply_to_every_layer() {
+    let opts = ApplyOptions {
+        array_merge: ArrayMerge::Union,
+        ..Default::default()
+    };
+    let source = json!({"hosts": ["a"]});
+    let transforms = [json!({"hosts": ["a", "b"]}), json!({"hosts": ["b", "c"]})];
+    let result = opts.apply_all(&source, &transforms).unwrap();
+    assert_eq!(result, json!({"hosts": ["a", "b", "c"]}));
+}
+
+#[test]
+fn error_reports_failing_layer() {
+    let source = json!({"a": 1});
+    let transforms = [
+        json!({"b": 2}),
+        json!({"c": 3}),
+        json!({"@jdt.remove": true}),
+    ];
+    let err = apply_all(&source, &transforms).unwrap_err();
+    match err {
+        JdtError::Layer { index, source } => {
+            assert_eq!(index, 2);
+            assert!(matches!(*source, JdtError::RootOperationNotAllowed));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn error_message_names_layer() {
+    let err = apply_all(&json!({}), &[json!([])]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "transform layer 0: transform must be a JSON object"
+    );
+}
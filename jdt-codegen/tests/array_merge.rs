@@ -0,0 +1,438 @@
+use jdt_codegen::{apply, apply_json_patch, apply_with_patch, ApplyOptions, ArrayMerge, JdtError};
+use serde_json::json;
+
+// ── Keyed merge in default transformation ───────────────────────────────
+
+#[test]
+fn keyed_default_merge_updates_matches_and_appends_new() {
+    let source = json!({
+        "Endpoints": [
+            {"Name": "api", "Url": "http://localhost:5000", "Timeout": 30},
+            {"Name": "auth", "Url": "http://localhost:5001"}
+        ]
+    });
+    let transform = json!({
+        "Endpoints": {
+            "@jdt.mergeKey": "Name",
+            "@jdt.value": [
+                {"Name": "api", "Url": "https://api.example.com"},
+                {"Name": "billing", "Url": "https://billing.example.com"}
+            ]
+        }
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(
+        result,
+        json!({
+            "Endpoints": [
+                {"Name": "api", "Url": "https://api.example.com", "Timeout": 30},
+                {"Name": "auth", "Url": "http://localhost:5001"},
+                {"Name": "billing", "Url": "https://billing.example.com"}
+            ]
+        })
+    );
+}
+
+#[test]
+fn keyed_default_merge_remove_unmatched() {
+    let source = json!({
+        "Endpoints": [
+            {"Name": "api", "Url": "a"},
+            {"Name": "auth", "Url": "b"},
+            {"Url": "no-name"}
+        ]
+    });
+    let transform = json!({
+        "Endpoints": {
+            "@jdt.mergeKey": "Name",
+            "@jdt.removeUnmatched": true,
+            "@jdt.value": [{"Name": "api", "Url": "c"}]
+        }
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"Endpoints": [{"Name": "api", "Url": "c"}]}));
+}
+
+#[test]
+fn keyed_default_merge_nested_objects_are_deep_merged() {
+    let source = json!({
+        "Items": [{"Id": 1, "Settings": {"a": 1, "b": 2}}]
+    });
+    let transform = json!({
+        "Items": {
+            "@jdt.mergeKey": "Id",
+            "@jdt.value": [{"Id": 1, "Settings": {"b": 3}}]
+        }
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(
+        result,
+        json!({"Items": [{"Id": 1, "Settings": {"a": 1, "b": 3}}]})
+    );
+}
+
+#[test]
+fn keyed_default_merge_missing_source_key_inserts_value() {
+    let source = json!({"other": true});
+    let transform = json!({
+        "Endpoints": {"@jdt.mergeKey": "Name", "@jdt.value": [{"Name": "api"}]}
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(
+        result,
+        json!({"other": true, "Endpoints": [{"Name": "api"}]})
+    );
+}
+
+#[test]
+fn keyed_default_merge_replaces_non_array_source() {
+    let source = json!({"Endpoints": {"Name": "api"}});
+    let transform = json!({
+        "Endpoints": {"@jdt.mergeKey": "Name", "@jdt.value": [{"Name": "api"}]}
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"Endpoints": [{"Name": "api"}]}));
+}
+
+#[test]
+fn keyed_default_merge_runs_verbs_in_new_elements() {
+    let source = json!({"Items": [{"n": 1, "v": 1}]});
+    let transform = json!({
+        "Items": {
+            "@jdt.mergeKey": "n",
+            "@jdt.value": [
+                {"n": 1, "@jdt.remove": "v"},
+                {"n": 2, "@jdt.remove": "v", "tags": {"@jdt.arrayMerge": "replace", "@jdt.value": ["a"]}}
+            ]
+        }
+    });
+    assert_eq!(
+        apply(&source, &transform).unwrap(),
+        json!({"Items": [{"n": 1}, {"n": 2, "tags": ["a"]}]})
+    );
+    let (result, patch) = apply_with_patch(&source, &transform).unwrap();
+    assert_eq!(apply_json_patch(&source, &patch).unwrap(), result);
+}
+
+// ── Keyed merge through @jdt.merge ──────────────────────────────────────
+
+#[test]
+fn keyed_merge_verb_with_selector() {
+    let source = json!({
+        "Service": {"Endpoints": [{"Name": "api", "Port": 80}]}
+    });
+    let transform = json!({
+        "@jdt.merge": {
+            "@jdt.path": "$.Service.Endpoints",
+            "@jdt.mergeKey": "Name",
+            "@jdt.value": [{"Name": "api", "Port": 443}, {"Name": "admin", "Port": 8443}]
+        }
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(
+        result,
+        json!({"Service": {"Endpoints": [
+            {"Name": "api", "Port": 443},
+            {"Name": "admin", "Port": 8443}
+        ]}})
+    );
+}
+
+#[test]
+fn keyed_merge_verb_without_key_appends() {
+    let source = json!({"Endpoints": [{"Name": "api"}]});
+    let transform = json!({
+        "@jdt.merge": {"@jdt.path": "$.Endpoints", "@jdt.value": [{"Name": "api"}]}
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(
+        result,
+        json!({"Endpoints": [{"Name": "api"}, {"Name": "api"}]})
+    );
+}
+
+// ── Per-apply policies ──────────────────────────────────────────────────
+
+fn apply_policy(
+    policy: ArrayMerge,
+    source: &serde_json::Value,
+    transform: &serde_json::Value,
+) -> serde_json::Value {
+    let opts = ApplyOptions {
+        array_merge: policy,
+        ..Default::default()
+    };
+    opts.apply(source, transform).unwrap()
+}
+
+#[test]
+fn policy_append_is_default() {
+    let source = json!({"arr": [1, 2]});
+    let transform = json!({"arr": [2, 3]});
+    assert_eq!(
+        apply_policy(ArrayMerge::Append, &source, &transform),
+        apply(&source, &transform).unwrap()
+    );
+}
+
+#[test]
+fn policy_replace() {
+    let source = json!({"arr": [1, 2], "nested": {"arr": [1]}});
+    let transform = json!({"arr": [3], "nested": {"arr": [4]}});
+    assert_eq!(
+        apply_policy(ArrayMerge::Replace, &source, &transform),
+        json!({"arr": [3], "nested": {"arr": [4]}})
+    );
+}
+
+#[test]
+fn policy_union() {
+    let source = json!({"arr": [1, 2, {"a": 1}]});
+    let transform = json!({"arr": [2, 3, 3, {"a": 1}]});
+    assert_eq!(
+        apply_policy(ArrayMerge::Union, &source, &transform),
+        json!({"arr": [1, 2, {"a": 1}, 3]})
+    );
+}
+
+#[test]
+fn policy_positional() {
+    let source = json!({"arr": [{"a": 1, "b": 1}, 2, [1]]});
+    let transform = json!({"arr": [{"b": 2}, 20, [2], "extra"]});
+    assert_eq!(
+        apply_policy(ArrayMerge::Positional, &source, &transform),
+        json!({"arr": [{"a": 1, "b": 2}, 20, [2], "extra"]})
+    );
+}
+
+#[test]
+fn policy_keyed_per_apply() {
+    let source = json!({"arr": [{"id": 1, "v": 1}]});
+    let transform = json!({"arr": [{"id": 1, "v": 2}, {"id": 2}]});
+    let policy = ArrayMerge::Keyed {
+        key: "id".into(),
+        remove_unmatched: false,
+    };
+    assert_eq!(
+        apply_policy(policy, &source, &transform),
+        json!({"arr": [{"id": 1, "v": 2}, {"id": 2}]})
+    );
+}
+
+#[test]
+fn policy_applies_to_merge_verb() {
+    let source = json!({"arr": [1, 2]});
+    let transform = json!({"@jdt.merge": {"@jdt.path": "$.arr", "@jdt.value": [2, 3]}});
+    assert_eq!(
+        apply_policy(ArrayMerge::Union, &source, &transform),
+        json!({"arr": [1, 2, 3]})
+    );
+}
+
+// ── Per-node overrides ──────────────────────────────────────────────────
+
+#[test]
+fn node_override_replace_in_default_merge() {
+    let source = json!({"a": [1, 2], "b": [1, 2]});
+    let transform = json!({
+        "a": {"@jdt.arrayMerge": "replace", "@jdt.value": [3]},
+        "b": [3]
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"a": [3], "b": [1, 2, 3]}));
+}
+
+#[test]
+fn node_override_wins_over_apply_default() {
+    let source = json!({"a": [1, 2]});
+    let transform = json!({"a": {"@jdt.arrayMerge": "append", "@jdt.value": [2]}});
+    assert_eq!(
+        apply_policy(ArrayMerge::Replace, &source, &transform),
+        json!({"a": [1, 2, 2]})
+    );
+}
+
+#[test]
+fn node_override_in_merge_verb() {
+    let source = json!({"a": [1, 2]});
+    let transform = json!({
+        "@jdt.merge": {"@jdt.path": "$.a", "@jdt.arrayMerge": "positional", "@jdt.value": [9]}
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"a": [9, 2]}));
+}
+
+#[test]
+fn node_override_keyed_explicit() {
+    let source = json!({"a": [{"k": 1}]});
+    let transform = json!({
+        "a": {"@jdt.arrayMerge": "keyed", "@jdt.mergeKey": "k", "@jdt.value": [{"k": 1, "x": 1}]}
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"a": [{"k": 1, "x": 1}]}));
+}
+
+#[test]
+fn node_override_under_new_key_inserts_value() {
+    let source = json!({"b": [1]});
+    let transform = json!({
+        "a": {"hosts": {"@jdt.arrayMerge": "replace", "@jdt.value": [1]}},
+        "b": [{"ports": {"@jdt.arrayMerge": "positional", "@jdt.value": [80]}}]
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(
+        result,
+        json!({"a": {"hosts": [1]}, "b": [1, {"ports": [80]}]})
+    );
+}
+
+fn hosts() -> serde_json::Value {
+    json!({"h": {"@jdt.arrayMerge": "replace", "@jdt.value": [1]}})
+}
+
+#[test]
+fn node_override_over_a_scalar_writes_value() {
+    let transform = json!({"a": hosts()});
+    let expected = json!({"a": {"h": [1]}});
+    assert_eq!(apply(&json!({"a": 5}), &transform).unwrap(), expected);
+    assert_eq!(apply(&json!({"a": {}}), &transform).unwrap(), expected);
+}
+
+#[test]
+fn node_override_in_replaced_array_writes_value() {
+    let source = json!({"a": [0]});
+    let transform = json!({"a": {"@jdt.arrayMerge": "replace", "@jdt.value": [hosts()]}});
+    assert_eq!(
+        apply(&source, &transform).unwrap(),
+        json!({"a": [{"h": [1]}]})
+    );
+}
+
+#[test]
+fn node_override_in_merge_verb_over_a_scalar_writes_value() {
+    let transform = json!({"@jdt.merge": {"@jdt.path": "$.a", "@jdt.value": hosts()}});
+    assert_eq!(
+        apply(&json!({"a": 5}), &transform).unwrap(),
+        json!({"a": {"h": [1]}})
+    );
+}
+
+#[test]
+fn node_override_in_replace_verb_writes_value() {
+    let expected = json!({"a": {"h": [1]}});
+    let selector = json!({"@jdt.replace": {"@jdt.path": "$.a", "@jdt.value": hosts()}});
+    assert_eq!(apply(&json!({"a": 5}), &selector).unwrap(), expected);
+    let plain = json!({"a": {"@jdt.replace": hosts()}});
+    assert_eq!(apply(&json!({"a": {}}), &plain).unwrap(), expected);
+    let nested = json!({"a": {"@jdt.replace": [[hosts()]]}});
+    assert_eq!(
+        apply(&json!({"a": {}}), &nested).unwrap(),
+        json!({"a": [{"h": [1]}]})
+    );
+}
+
+#[test]
+fn positional_value_keyed_by_index() {
+    let source = json!({"a": ["x", {"k": 1}, "z"]});
+    let transform = json!({
+        "a": {"@jdt.arrayMerge": "positional", "@jdt.value": {"1": {"v": 2}, "4": "e"}}
+    });
+    assert_eq!(
+        apply(&source, &transform).unwrap(),
+        json!({"a": ["x", {"k": 1, "v": 2}, "z", null, "e"]})
+    );
+    let (result, patch) = apply_with_patch(&source, &transform).unwrap();
+    assert_eq!(apply_json_patch(&source, &patch).unwrap(), result);
+    // Without an array to merge into, elements with gaps between their indices keep them.
+    assert_eq!(
+        apply(&json!({}), &transform).unwrap(),
+        json!({"a": {"1": {"v": 2}, "4": "e"}})
+    );
+    let dense = json!({"a": {"@jdt.arrayMerge": "positional", "@jdt.value": {"1": "y", "0": "x"}}});
+    assert_eq!(apply(&json!({}), &dense).unwrap(), json!({"a": ["x", "y"]}));
+    // A source object is merged by name.
+    assert_eq!(
+        apply(&json!({"a": {"1": {"k": 1}, "2": "z"}}), &transform).unwrap(),
+        json!({"a": {"1": {"k": 1, "v": 2}, "2": "z", "4": "e"}})
+    );
+    // Other policies keep an object value as it is.
+    let transform = json!({"a": {"@jdt.arrayMerge": "append", "@jdt.value": {"1": "y"}}});
+    assert_eq!(
+        apply(&source, &transform).unwrap(),
+        json!({"a": {"1": "y"}})
+    );
+}
+
+#[test]
+fn positional_element_keeps_its_own_policy() {
+    let source = json!({"grid": [["a", "b"], ["c"]]});
+    let transform = json!({
+        "grid": {
+            "@jdt.arrayMerge": "positional",
+            "@jdt.value": [
+                {"@jdt.arrayMerge": "positional", "@jdt.value": ["x"]},
+                {"@jdt.arrayMerge": "replace", "@jdt.value": ["y"]}
+            ]
+        }
+    });
+    assert_eq!(
+        apply(&source, &transform).unwrap(),
+        json!({"grid": [["x", "b"], ["y"]]})
+    );
+    let (result, patch) = apply_with_patch(&source, &transform).unwrap();
+    assert_eq!(apply_json_patch(&source, &patch).unwrap(), result);
+}
+
+// ── Errors ──────────────────────────────────────────────────────────────
+
+#[test]
+fn error_merge_key_not_string() {
+    let source = json!({"Endpoints": []});
+    let transform = json!({"Endpoints": {"@jdt.mergeKey": 1, "@jdt.value": []}});
+    let err = apply(&source, &transform).unwrap_err();
+    assert!(matches!(err, JdtError::AttributeNotString(_)));
+}
+
+#[test]
+fn error_merge_key_missing_value() {
+    let source = json!({"Endpoints": []});
+    let transform = json!({"Endpoints": {"@jdt.mergeKey": "Name"}});
+    let err = apply(&source, &transform).unwrap_err();
+    assert!(matches!(err, JdtError::MissingAttribute(_)));
+}
+
+#[test]
+fn error_remove_unmatched_not_bool() {
+    let source = json!({"Endpoints": []});
+    let transform = json!({
+        "Endpoints": {"@jdt.mergeKey": "Name", "@jdt.removeUnmatched": "yes", "@jdt.value": []}
+    });
+    let err = apply(&source, &transform).unwrap_err();
+    assert!(matches!(err, JdtError::AttributeNotBool(_)));
+}
+
+#[test]
+fn error_unknown_array_merge_policy() {
+    let source = json!({"a": []});
+    let transform = json!({"a": {"@jdt.arrayMerge": "zip", "@jdt.value": []}});
+    let err = apply(&source, &transform).unwrap_err();
+    assert!(matches!(err, JdtError::InvalidAttributeValue(_, _)));
+}
+
+#[test]
+fn error_keyed_policy_without_merge_key() {
+    let source = json!({"a": []});
+    let transform = json!({"a": {"@jdt.arrayMerge": "keyed", "@jdt.value": []}});
+    let err = apply(&source, &transform).unwrap_err();
+    assert!(matches!(err, JdtError::MissingAttribute(_)));
+}
+
+#[test]
+fn error_merge_key_with_conflicting_policy() {
+    let source = json!({"a": []});
+    let transform = json!({
+        "a": {"@jdt.arrayMerge": "union", "@jdt.mergeKey": "k", "@jdt.value": []}
+    });
+    let err = apply(&source, &transform).unwrap_err();
+    assert!(matches!(err, JdtError::InvalidAttributeValue(_, _)));
+}
@@ -0,0 +1,189 @@
+use jdt_codegen::{apply, ApplyOptions, JdtError, RenameCollision};
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+
+fn ignoring_case() -> ApplyOptions {
+    ApplyOptions {
+        case_insensitive: true,
+        ..Default::default()
+    }
+}
+
+fn apply_ignoring_case(source: &Value, transform: &Value) -> Value {
+    ignoring_case().apply(source, transform).unwrap()
+}
+
+#[test]
+fn default_merge_keeps_the_source_spelling() {
+    let source = json!({
+        "ConnectionStrings": {"Default": "Server=local"},
+        "Logging": {"LogLevel": {"Default": "Warning"}}
+    });
+    let transform = json!({
+        "connectionStrings": {"default": "Server=prod"},
+        "LOGGING": {"loglevel": {"Microsoft": "Error"}},
+        "newSection": {"Key": 1}
+    });
+    assert_eq!(
+        apply_ignoring_case(&source, &transform),
+        json!({
+            "ConnectionStrings": {"Default": "Server=prod"},
+            "Logging": {"LogLevel": {"Default": "Warning", "Microsoft": "Error"}},
+            "newSection": {"Key": 1}
+        })
+    );
+}
+
+#[test]
+fn exact_matching_is_the_default() {
+    let source = json!({"Port": 80});
+    assert_eq!(
+        apply(&source, &json!({"port": 8080})).unwrap(),
+        json!({"Port": 80, "port": 8080})
+    );
+}
+
+#[test]
+fn an_exact_spelling_wins_over_other_cases() {
+    let source = json!({"KEY": 1, "key": 2});
+    assert_eq!(
+        apply_ignoring_case(&source, &json!({"key": 3})),
+        json!({"KEY": 1, "key": 3})
+    );
+    assert_eq!(
+        apply_ignoring_case(&source, &json!({"@jdt.remove": "Key"})),
+        json!({"key": 2})
+    );
+}
+
+#[test]
+fn remove_matches_names_ignoring_case() {
+    let source = json!({"Debug": true, "Port": 80});
+    assert_eq!(
+        apply_ignoring_case(&source, &json!({"@jdt.remove": ["debug", "PORT"]})),
+        json!({})
+    );
+}
+
+#[test]
+fn rename_writes_the_new_name_as_given() {
+    let source = json!({"ConnString": "a", "other": 1});
+    assert_eq!(
+        apply_ignoring_case(
+            &source,
+            &json!({"@jdt.rename": {"connstring": "ConnectionString"}})
+        ),
+        json!({"ConnectionString": "a", "other": 1})
+    );
+    // Changing only the case is a rename, not a collision.
+    let opts = ApplyOptions {
+        rename_collision: RenameCollision::Error,
+        ..ignoring_case()
+    };
+    assert_eq!(
+        opts.apply(&source, &json!({"@jdt.rename": {"OTHER": "Other"}}))
+            .unwrap(),
+        json!({"ConnString": "a", "Other": 1})
+    );
+}
+
+#[test]
+fn rename_onto_another_spelling_collides() {
+    let source = json!({"old": 1, "TARGET": 2});
+    let transform = json!({"@jdt.rename": {"old": "Target"}});
+    assert_eq!(
+        apply_ignoring_case(&source, &transform),
+        json!({"Target": 1})
+    );
+
+    let error = ApplyOptions {
+        rename_collision: RenameCollision::Error,
+        ..ignoring_case()
+    };
+    assert!(matches!(
+        error.apply(&source, &transform),
+        Err(JdtError::RenameCollision(name)) if name == "Target"
+    ));
+
+    let source = json!({"old": {"a": 1}, "TARGET": {"A": 0, "b": 2}});
+    let merge = ApplyOptions {
+        rename_collision: RenameCollision::Merge,
+        ..ignoring_case()
+    };
+    assert_eq!(
+        merge.apply(&source, &transform).unwrap(),
+        json!({"TARGET": {"A": 1, "b": 2}})
+    );
+}
+
+#[test]
+fn selectors_match_names_ignoring_case() {
+    let source = json!({
+        "Databases": [
+            {"Name": "main", "Primary": true, "Host": "a"},
+            {"Name": "replica", "Primary": false, "Host": "b"}
+        ]
+    });
+    let transform = json!({
+        "@jdt.replace": {
+            "@jdt.path": "$.databases[?(@.primary == true)].HOST",
+            "@jdt.expect": 1,
+            "@jdt.value": "db.example.com"
+        },
+        "@jdt.rename": {"@jdt.path": "$.DATABASES[1].name", "@jdt.value": "Id"}
+    });
+    assert_eq!(
+        apply_ignoring_case(&source, &transform),
+        json!({
+            "Databases": [
+                {"Name": "main", "Primary": true, "Host": "db.example.com"},
+                {"Id": "replica", "Primary": false, "Host": "b"}
+            ]
+        })
+    );
+    assert!(matches!(
+        apply(&source, &transform),
+        Err(JdtError::UnexpectedMatchCount { actual: 0, .. })
+    ));
+}
+
+#[test]
+fn keyed_merge_finds_the_key_field_ignoring_case() {
+    let source = json!({"Endpoints": [{"Name": "api", "Url": "http://api"}]});
+    let transform = json!({
+        "endpoints": {"@jdt.mergeKey": "name", "@jdt.value": [{"NAME": "api", "url": "https://api"}]}
+    });
+    assert_eq!(
+        apply_ignoring_case(&source, &transform),
+        json!({"Endpoints": [{"Name": "api", "Url": "https://api"}]})
+    );
+}
+
+#[test]
+fn patches_and_text_use_the_source_spelling() {
+    let opts = ignoring_case();
+    let source = json!({"Kestrel": {"Port": 80}});
+    let (_, patch) = opts
+        .apply_with_patch(&source, &json!({"kestrel": {"port": 8080}}))
+        .unwrap();
+    assert_eq!(
+        patch,
+        json!([{"op": "replace", "path": "/Kestrel/Port", "value": 8080}])
+    );
+
+    let text = "{\n  \"Kestrel\": { \"Port\": 80 }\n}\n";
+    assert_eq!(
+        opts.apply_text(text, &json!({"KESTREL": {"PORT": 8080}}))
+            .unwrap(),
+        "{\n  \"Kestrel\": { \"Port\": 8080 }\n}\n"
+    );
+}
+
+#[test]
+fn non_ascii_names_compare_one_character_at_a_time() {
+    let source = json!({"Größe": 1});
+    assert_eq!(
+        apply_ignoring_case(&source, &json!({"GRÖSSE": 2, "größe": 3})),
+        json!({"Größe": 3, "GRÖSSE": 2})
+    );
+}
@@ -0,0 +1,53 @@
+//! Helpers shared by the integration tests; each test crate uses only some of them.
+#![allow(dead_code)]
+
+use proptest::prelude::*;
+use serde_json::{Map, Value};
+
+/// RFC 7396 reference implementation.
+pub fn merge_patch(target: &Value, patch: &Value) -> Value {
+    let Some(patch) = patch.as_object() else {
+        return patch.clone();
+    };
+    let mut out = target.as_object().cloned().unwrap_or_default();
+    for (k, v) in patch {
+        if v.is_null() {
+            out.remove(k);
+        } else {
+            let merged = merge_patch(out.get(k).unwrap_or(&Value::Null), v);
+            out.insert(k.clone(), merged);
+        }
+    }
+    Value::Object(out)
+}
+
+/// Short names, some starting with `@`, leaving out the `@jdt.` names a transform reads
+/// as syntax.
+pub fn key() -> impl Strategy<Value = String> {
+    "@?(jdt\\.)?[a-d]".prop_filter("@jdt. names are JDT syntax", |k| !k.starts_with("@jdt."))
+}
+
+pub fn json_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        (-5i64..5).prop_map(Value::from),
+        "[a-c]{0,2}".prop_map(Value::from),
+    ];
+    leaf.prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+            json_object(inner),
+        ]
+    })
+}
+
+/// An object of [`json_value`]s, as accepted for a source or transform.
+pub fn json_document() -> impl Strategy<Value = Value> {
+    json_object(json_value())
+}
+
+pub fn json_object(values: impl Strategy<Value = Value>) -> impl Strategy<Value = Value> {
+    prop::collection::btree_map(key(), values, 0..5)
+        .prop_map(|m| Value::Object(m.into_iter().collect::<Map<_, _>>()))
+}
@@ -0,0 +1,252 @@
+use jdt_codegen::{apply, compose, JdtError};
+use proptest::prelude::*;
+use serde_json::{json, Map, Value};
+
+mod common;
+use common::{json_document, json_value};
+
+fn sources() -> Vec<Value> {
+    vec![
+        json!({}),
+        json!({"a": 1, "b": 2, "old": "x", "A": {"x": 1, "list": [1]}, "list": [0]}),
+        json!({"A": 5, "list": "scalar", "keep": true}),
+        json!({"A": [1, 2], "B": {"deep": {"y": null}}}),
+    ]
+}
+
+/// Assert `compose(t1, t2)` behaves like applying `t1` then `t2` on every sample source.
+fn assert_composes(t1: &Value, t2: &Value) -> Value {
+    let composed = compose(t1, t2).unwrap();
+    for source in sources() {
+        let sequential = apply(&source, t1).and_then(|mid| apply(&mid, t2));
+        let combined = apply(&source, &composed);
+        match (sequential, combined) {
+            (Ok(expected), Ok(actual)) => assert_eq!(actual, expected, "source {source}"),
+            (Err(_), Err(_)) => {}
+            (expected, actual) => panic!("source {source}: {expected:?} vs {actual:?}"),
+        }
+    }
+    composed
+}
+
+fn issues(t1: &Value, t2: &Value) -> Vec<String> {
+    match compose(t1, t2).unwrap_err() {
+        JdtError::NotComposable(issues) => issues,
+        other => panic!("unexpected error: {other}"),
+    }
+}
+
+// ── Default transformations ─────────────────────────────────────────────
+
+#[test]
+fn later_scalars_win() {
+    let composed = assert_composes(&json!({"a": 10, "c": 3}), &json!({"a": 20, "d": 4}));
+    assert_eq!(composed, json!({"a": 20, "c": 3, "d": 4}));
+}
+
+#[test]
+fn nested_objects_compose_recursively() {
+    let composed = assert_composes(
+        &json!({"A": {"x": 2, "y": {"p": 1}}}),
+        &json!({"A": {"y": {"q": 2}, "z": 3}, "B": {"deep": {"y": 1}}}),
+    );
+    assert_eq!(
+        composed,
+        json!({"A": {"x": 2, "y": {"p": 1, "q": 2}, "z": 3}, "B": {"deep": {"y": 1}}})
+    );
+}
+
+#[test]
+fn arrays_concatenate() {
+    let composed = assert_composes(&json!({"list": [1]}), &json!({"list": [2, 3]}));
+    assert_eq!(composed, json!({"list": [1, 2, 3]}));
+}
+
+#[test]
+fn empty_transform_is_identity() {
+    let t = json!({"a": 1, "@jdt.remove": "b"});
+    assert_eq!(assert_composes(&json!({}), &t), t);
+    assert_eq!(assert_composes(&t, &json!({})), t);
+}
+
+#[test]
+fn replace_policy_wrapper_overrides() {
+    let t2 = json!({"list": {"@jdt.arrayMerge": "replace", "@jdt.value": [9]}});
+    assert_eq!(assert_composes(&json!({"list": [1]}), &t2), t2);
+}
+
+// ── Verbs ───────────────────────────────────────────────────────────────
+
+#[test]
+fn named_removes_are_combined() {
+    let composed = assert_composes(
+        &json!({"@jdt.remove": "a", "c": 1}),
+        &json!({"@jdt.remove": ["b", "c"]}),
+    );
+    assert_eq!(composed, json!({"@jdt.remove": ["a", "b", "c"]}));
+}
+
+#[test]
+fn named_renames_run_in_order() {
+    let composed = assert_composes(
+        &json!({"@jdt.rename": {"a": "alpha"}}),
+        &json!({"@jdt.rename": {"b": "beta"}, "c": 1}),
+    );
+    assert_eq!(
+        composed,
+        json!({"@jdt.rename": [{"a": "alpha"}, {"b": "beta"}], "c": 1})
+    );
+}
+
+#[test]
+fn selector_verbs_in_first_transform_are_kept() {
+    let t1 = json!({
+        "@jdt.merge": {"@jdt.path": "$.A", "@jdt.value": {"merged": true}},
+        "@jdt.replace": {"@jdt.path": "$.b", "@jdt.value": 0}
+    });
+    let composed = assert_composes(&t1, &json!({"a": 2, "list": [5]}));
+    assert_eq!(composed["@jdt.merge"], t1["@jdt.merge"]);
+}
+
+#[test]
+fn whole_node_replace_absorbs_second_transform() {
+    let composed = assert_composes(
+        &json!({"@jdt.replace": {"x": 1, "list": [1]}}),
+        &json!({"y": 2, "list": [2], "@jdt.rename": {"x": "z"}}),
+    );
+    assert_eq!(
+        composed,
+        json!({"@jdt.replace": {"z": 1, "y": 2, "list": [1, 2]}})
+    );
+}
+
+#[test]
+fn second_whole_node_replace_wins() {
+    let t2 = json!({"@jdt.replace": {"only": true}});
+    assert_eq!(
+        assert_composes(&json!({"a": 5, "@jdt.remove": "b"}), &t2),
+        t2
+    );
+}
+
+// ── Not composable ──────────────────────────────────────────────────────
+
+#[test]
+fn error_selector_verb_in_second_transform() {
+    let found = issues(
+        &json!({"a": 1}),
+        &json!({"@jdt.remove": {"@jdt.path": "$.a"}}),
+    );
+    assert_eq!(found.len(), 1);
+    assert!(found[0].starts_with("$: @jdt.remove"));
+}
+
+#[test]
+fn error_nested_verbs_under_shared_key() {
+    let found = issues(&json!({"A": {"@jdt.remove": "x"}}), &json!({"A": {"y": 1}}));
+    assert!(found[0].starts_with("$.A:"));
+}
+
+#[test]
+fn error_lists_every_construct() {
+    let found = issues(
+        &json!({"A": 1, "list": {"@jdt.mergeKey": "id", "@jdt.value": []}}),
+        &json!({"A": {"x": 1}, "list": [1], "@jdt.merge": {"b": 1}}),
+    );
+    assert_eq!(found.len(), 3, "{found:?}");
+    let message = JdtError::NotComposable(found).to_string();
+    assert!(
+        message.contains("$.A") && message.contains("$.list") && message.contains("@jdt.merge")
+    );
+}
+
+#[test]
+fn error_array_after_scalar() {
+    let found = issues(&json!({"list": 1}), &json!({"list": [2]}));
+    assert!(found[0].starts_with("$.list:"));
+}
+
+#[test]
+fn error_remove_after_rename() {
+    let found = issues(
+        &json!({"@jdt.rename": {"old": "new"}}),
+        &json!({"@jdt.remove": "new"}),
+    );
+    assert_eq!(found.len(), 1);
+}
+
+#[test]
+fn error_nested_change_after_selector_verb() {
+    let found = issues(
+        &json!({"@jdt.rename": {"@jdt.path": "$.A", "@jdt.value": "B"}}),
+        &json!({"B": {"x": 1}}),
+    );
+    assert!(found[0].starts_with("$.B:"));
+}
+
+#[test]
+fn error_nested_change_before_rename_in_first() {
+    let found = issues(
+        &json!({"@jdt.rename": {"b": "c"}, "b": {"a": null}}),
+        &json!({"b": null}),
+    );
+    assert!(found[0].starts_with("$.b:"));
+}
+
+#[test]
+fn error_transform_not_object() {
+    let err = compose(&json!([]), &json!({})).unwrap_err();
+    assert!(matches!(err, JdtError::TransformNotObject));
+}
+
+// ── Property tests ──────────────────────────────────────────────────────
+
+/// An object over few names, so that the keys of a source and two transforms meet.
+fn few_keys(values: impl Strategy<Value = Value>) -> impl Strategy<Value = Value> {
+    prop::collection::btree_map("[ab]", values, 0..3)
+        .prop_map(|m| Value::Object(m.into_iter().collect::<Map<_, _>>()))
+}
+
+/// A transform with named removes and renames at its root.
+fn transform() -> impl Strategy<Value = Value> {
+    let removes = prop::option::weighted(0.3, prop::collection::vec("[abc]", 1..2));
+    let renames = prop::option::of(prop::collection::btree_map("[ab]", "[bc]", 1..2));
+    (
+        few_keys(prop_oneof![
+            Just(Value::Null),
+            json_value(),
+            json_document()
+        ]),
+        removes,
+        renames,
+    )
+        .prop_map(|(mut t, removes, renames)| {
+            let obj = t.as_object_mut().unwrap();
+            if let Some(names) = removes {
+                obj.insert("@jdt.remove".into(), json!(names));
+            }
+            if let Some(pairs) = renames {
+                obj.insert("@jdt.rename".into(), json!(pairs));
+            }
+            t
+        })
+}
+
+proptest! {
+    #[test]
+    fn composed_transform_matches_sequential_application(
+        source in few_keys(prop_oneof![json_value(), json_document()]),
+        t1 in transform(),
+        t2 in transform(),
+    ) {
+        let Ok(composed) = compose(&t1, &t2) else {
+            return Ok(());
+        };
+        let sequential = apply(&source, &t1).and_then(|mid| apply(&mid, &t2));
+        match (sequential, apply(&source, &composed)) {
+            (Ok(expected), Ok(actual)) => prop_assert_eq!(actual, expected),
+            (Err(_), Err(_)) => {}
+            (expected, actual) => prop_assert!(false, "{:?} vs {:?}", expected, actual),
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use jdt_codegen::{apply, flatten_config, unflatten_config, JdtError, KeyStyle};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+fn pairs(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+    entries
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn flattens_to_colon_keys() {
+    let value = json!({
+        "Logging": {"LogLevel": {"Default": "Warning"}},
+        "Kestrel": {"Port": 8080, "Tls": true, "Cert": null},
+        "AllowedHosts": ["a.example.com", "b.example.com"],
+        "Empty": {}
+    });
+    let mut flat = flatten_config(&value, KeyStyle::Colon).unwrap();
+    flat.sort();
+    assert_eq!(
+        flat,
+        pairs(&[
+            ("AllowedHosts:0", "a.example.com"),
+            ("AllowedHosts:1", "b.example.com"),
+            ("Kestrel:Cert", ""),
+            ("Kestrel:Port", "8080"),
+            ("Kestrel:Tls", "true"),
+            ("Logging:LogLevel:Default", "Warning"),
+        ])
+    );
+}
+
+#[test]
+fn flattens_to_env_vars() {
+    let value = json!({"Endpoints": [{"Name": "api", "Url": "http://api"}]});
+    assert_eq!(
+        flatten_config(&value, KeyStyle::EnvVar).unwrap(),
+        pairs(&[
+            ("Endpoints__0__Name", "api"),
+            ("Endpoints__0__Url", "http://api"),
+        ])
+    );
+}
+
+#[test]
+fn names_holding_a_separator_are_rejected() {
+    let err = flatten_config(&json!({"A": {"b:c": 1}}), KeyStyle::Colon).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "configuration key \"A:b:c\": the name \"b:c\" contains a section separator"
+    );
+    let err = flatten_config(&json!({"my__key": 1}), KeyStyle::EnvVar).unwrap_err();
+    assert!(matches!(err, JdtError::ConfigKey { ref key, .. } if key == "my__key"));
+    assert!(matches!(
+        flatten_config(&json!([1]), KeyStyle::Colon),
+        Err(JdtError::SourceNotObject)
+    ));
+}
+
+#[test]
+fn env_vars_become_a_transform() {
+    let transform = unflatten_config(
+        [
+            ("Logging__LogLevel__Default", "Debug"),
+            ("ConnectionStrings:Default", "Server=db"),
+            ("AllowedHosts__1", "override.example.com"),
+            ("AllowedHosts__0", "first.example.com"),
+        ],
+        KeyStyle::EnvVar,
+    )
+    .unwrap();
+    assert_eq!(
+        transform,
+        json!({
+            "AllowedHosts": {
+                "@jdt.arrayMerge": "positional",
+                "@jdt.value": ["first.example.com", "override.example.com"]
+            },
+            "ConnectionStrings": {"Default": "Server=db"},
+            "Logging": {"LogLevel": {"Default": "Debug"}}
+        })
+    );
+    let source = json!({
+        "Logging": {"LogLevel": {"Default": "Warning", "Microsoft": "Error"}},
+        "AllowedHosts": ["a", "b", "c"]
+    });
+    assert_eq!(
+        apply(&source, &transform).unwrap(),
+        json!({
+            "Logging": {"LogLevel": {"Default": "Debug", "Microsoft": "Error"}},
+            "AllowedHosts": ["first.example.com", "override.example.com", "c"],
+            "ConnectionStrings": {"Default": "Server=db"}
+        })
+    );
+}
+
+#[test]
+fn array_elements_merge_by_position() {
+    let transform = unflatten_config(
+        [
+            ("Endpoints:0:Url", "https://api"),
+            ("Endpoints:1:Url", "https://auth"),
+        ],
+        KeyStyle::Colon,
+    )
+    .unwrap();
+    let source = json!({"Endpoints": [{"Name": "api", "Url": "http://api"}]});
+    assert_eq!(
+        apply(&source, &transform).unwrap(),
+        json!({"Endpoints": [{"Name": "api", "Url": "https://api"}, {"Url": "https://auth"}]})
+    );
+}
+
+#[test]
+fn nested_arrays_merge_by_position() {
+    let transform =
+        unflatten_config([("Grid__0__0", "x"), ("Grid__0__1", "y")], KeyStyle::EnvVar).unwrap();
+    assert_eq!(
+        apply(&json!({"Grid": [["a", "b"]]}), &transform).unwrap(),
+        json!({"Grid": [["x", "y"]]})
+    );
+    let transform = unflatten_config([("Grid:1:1", "z")], KeyStyle::Colon).unwrap();
+    assert_eq!(
+        apply(&json!({"Grid": [["a"], ["b", "c"], ["d"]]}), &transform).unwrap(),
+        json!({"Grid": [["a"], ["b", "z"], ["d"]]})
+    );
+}
+
+#[test]
+fn colon_style_leaves_double_underscores_alone() {
+    assert_eq!(
+        unflatten_config([("A__B", "1")], KeyStyle::Colon).unwrap(),
+        json!({"A__B": "1"})
+    );
+}
+
+#[test]
+fn round_trips_through_env_vars() {
+    let value = json!({
+        "Serilog": {"MinimumLevel": "Information", "WriteTo": [{"Name": "Console"}, {"Name": "File"}]},
+        "Port": "8080"
+    });
+    let flat = flatten_config(&value, KeyStyle::EnvVar).unwrap();
+    let transform = unflatten_config(flat, KeyStyle::EnvVar).unwrap();
+    assert_eq!(apply(&json!({}), &transform).unwrap(), value);
+
+    let value = json!({"Matrix": [["a", "b"], ["c"]], "Routes": [{"Hosts": ["x", "y"]}]});
+    let flat = flatten_config(&value, KeyStyle::Colon).unwrap();
+    let transform = unflatten_config(flat, KeyStyle::Colon).unwrap();
+    assert_eq!(
+        apply(&json!({"Other": 1}), &transform).unwrap()["Routes"],
+        value["Routes"]
+    );
+    assert_eq!(apply(&json!({}), &transform).unwrap(), value);
+}
+
+#[test]
+fn conflicting_keys_are_rejected() {
+    let err = unflatten_config([("A", "1"), ("A__B", "2")], KeyStyle::EnvVar).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "configuration key \"A__B\": a parent section already has a value"
+    );
+    let err = unflatten_config([("A:B", "2"), ("A", "1")], KeyStyle::Colon).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "configuration key \"A\": the key is also a section"
+    );
+    let err = unflatten_config([("A__B", "1"), ("A:B", "2")], KeyStyle::EnvVar).unwrap_err();
+    assert!(matches!(err, JdtError::ConfigKey { ref key, .. } if key == "A:B"));
+}
+
+#[test]
+fn section_names_ignore_case() {
+    let err = unflatten_config([("A:b", "1"), ("a:B", "2")], KeyStyle::Colon).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "configuration key \"a:B\": the key is set more than once"
+    );
+    assert_eq!(
+        unflatten_config(
+            [("Logging__Level", "1"), ("LOGGING__Format", "2")],
+            KeyStyle::EnvVar
+        )
+        .unwrap(),
+        json!({"Logging": {"Format": "2", "Level": "1"}})
+    );
+    let err = unflatten_config([("A", "1"), ("a:B", "2")], KeyStyle::Colon).unwrap_err();
+    assert!(matches!(err, JdtError::ConfigKey { ref key, .. } if key == "a:B"));
+}
+
+#[test]
+fn empty_sections_are_rejected() {
+    let err = unflatten_config([("A__", "1")], KeyStyle::EnvVar).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "configuration key \"A__\": a section name is empty"
+    );
+    for key in ["", ":A", "A::B", "A:"] {
+        let err = unflatten_config([(key, "1")], KeyStyle::Colon).unwrap_err();
+        assert!(
+            matches!(err, JdtError::ConfigKey { key: ref k, .. } if k == key),
+            "{key:?}"
+        );
+    }
+    let err = flatten_config(&json!({"A": {"": 1}}), KeyStyle::Colon).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "configuration key \"A:\": a section name is empty"
+    );
+}
+
+#[test]
+fn sparse_indices_leave_other_elements_alone() {
+    let transform = unflatten_config([("Hosts__1", "x")], KeyStyle::EnvVar).unwrap();
+    assert_eq!(
+        transform,
+        json!({"Hosts": {"@jdt.arrayMerge": "positional", "@jdt.value": {"1": "x"}}})
+    );
+    assert_eq!(
+        apply(&json!({"Hosts": ["a", "b"]}), &transform).unwrap(),
+        json!({"Hosts": ["a", "x"]})
+    );
+    assert_eq!(
+        apply(&json!({"Hosts": ["a"]}), &transform).unwrap(),
+        json!({"Hosts": ["a", "x"]})
+    );
+    // Without an array, the index is kept as a name rather than shifted to 0.
+    assert_eq!(
+        apply(&json!({}), &transform).unwrap(),
+        json!({"Hosts": {"1": "x"}})
+    );
+    assert_eq!(
+        apply(&json!({"Hosts": "a"}), &transform).unwrap(),
+        json!({"Hosts": {"1": "x"}})
+    );
+
+    let entries = [("Hosts:10", "k"), ("Hosts:2", "c"), ("Hosts:0", "a")];
+    let transform = unflatten_config(entries, KeyStyle::Colon).unwrap();
+    let source = json!({"Hosts": ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10"]});
+    assert_eq!(
+        apply(&source, &transform).unwrap(),
+        json!({"Hosts": ["a", "1", "c", "3", "4", "5", "6", "7", "8", "9", "k"]})
+    );
+    // Leading zeros are names, not indices.
+    assert_eq!(
+        unflatten_config([("Codes:01", "x")], KeyStyle::Colon).unwrap(),
+        json!({"Codes": {"01": "x"}})
+    );
+}
+
+#[test]
+fn indices_past_the_end_keep_their_position() {
+    let transform =
+        unflatten_config([("Hosts__1", "B"), ("Hosts__5", "F")], KeyStyle::EnvVar).unwrap();
+    let result = apply(&json!({"Hosts": ["a", "b", "c"]}), &transform).unwrap();
+    assert_eq!(result, json!({"Hosts": ["a", "B", "c", null, null, "F"]}));
+    assert_eq!(
+        flatten_config(&result, KeyStyle::EnvVar).unwrap()[5],
+        ("Hosts__5".to_string(), "F".to_string())
+    );
+}
+
+#[test]
+fn numeric_keys_merge_into_objects_by_name() {
+    let transform = unflatten_config([("Ports__8080__Enabled", "true")], KeyStyle::EnvVar).unwrap();
+    let source = json!({"Ports": {"8080": {"Name": "a"}, "9090": {"Name": "b"}}});
+    assert_eq!(
+        apply(&source, &transform).unwrap(),
+        json!({"Ports": {"8080": {"Name": "a", "Enabled": "true"}, "9090": {"Name": "b"}}})
+    );
+    let transform =
+        unflatten_config([("Slots:0", "x"), ("Slots:1:On", "y")], KeyStyle::Colon).unwrap();
+    let source = json!({"Slots": {"0": "a", "1": {"Id": 1}, "2": "c"}});
+    assert_eq!(
+        apply(&source, &transform).unwrap(),
+        json!({"Slots": {"0": "x", "1": {"Id": 1, "On": "y"}, "2": "c"}})
+    );
+    // Nested sections merge into objects inside arrays as well.
+    let transform = unflatten_config([("Grid__0__1", "z")], KeyStyle::EnvVar).unwrap();
+    assert_eq!(
+        apply(&json!({"Grid": [{"0": "a", "1": "b"}]}), &transform).unwrap(),
+        json!({"Grid": [{"0": "a", "1": "z"}]})
+    );
+}
+
+#[test]
+fn sparse_sections_on_missing_keys_keep_their_indices() {
+    let transform = unflatten_config(
+        [("Hosts__1", "b"), ("Routes__2__Hosts__1", "c")],
+        KeyStyle::EnvVar,
+    )
+    .unwrap();
+    let result = apply(&json!({}), &transform).unwrap();
+    assert_eq!(
+        result,
+        json!({"Hosts": {"1": "b"}, "Routes": {"2": {"Hosts": {"1": "c"}}}})
+    );
+    assert_eq!(
+        flatten_config(&result, KeyStyle::Colon).unwrap(),
+        pairs(&[("Hosts:1", "b"), ("Routes:2:Hosts:1", "c")])
+    );
+}
@@ -0,0 +1,112 @@
+use jdt_codegen::{apply, diff_to_transform, JdtError};
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+mod common;
+use common::json_document;
+
+fn assert_roundtrip(before: &Value, after: &Value) -> Value {
+    let transform = diff_to_transform(before, after).unwrap();
+    assert_eq!(
+        apply(before, &transform).unwrap(),
+        *after,
+        "transform {transform}"
+    );
+    transform
+}
+
+// ── Generated transforms ────────────────────────────────────────────────
+
+#[test]
+fn identical_documents_give_empty_transform() {
+    let doc = json!({"a": 1, "b": {"c": [1, 2]}});
+    assert_eq!(assert_roundtrip(&doc, &doc), json!({}));
+}
+
+#[test]
+fn additions_and_overrides_use_default_merge() {
+    let before = json!({"Logging": {"Level": "Info", "Sinks": ["console"]}, "Port": 80});
+    let after =
+        json!({"Logging": {"Level": "Debug", "Sinks": ["console"]}, "Port": 80, "Tls": true});
+    let transform = assert_roundtrip(&before, &after);
+    assert_eq!(
+        transform,
+        json!({"Logging": {"Level": "Debug"}, "Tls": true})
+    );
+}
+
+#[test]
+fn deletions_use_named_remove() {
+    let before = json!({"a": 1, "b": 2, "c": {"d": 3, "e": 4}});
+    let after = json!({"c": {"e": 4}});
+    let transform = assert_roundtrip(&before, &after);
+    assert_eq!(
+        transform,
+        json!({"@jdt.remove": ["a", "b"], "c": {"@jdt.remove": "d"}})
+    );
+}
+
+#[test]
+fn array_growth_appends_new_elements() {
+    let before = json!({"Hosts": ["a", "b"]});
+    let after = json!({"Hosts": ["a", "b", "c"]});
+    assert_eq!(assert_roundtrip(&before, &after), json!({"Hosts": ["c"]}));
+}
+
+#[test]
+fn other_array_changes_replace_the_array() {
+    let before = json!({"Hosts": ["a", "b"]});
+    let after = json!({"Hosts": ["b"]});
+    assert_eq!(
+        assert_roundtrip(&before, &after),
+        json!({"Hosts": {"@jdt.arrayMerge": "replace", "@jdt.value": ["b"]}})
+    );
+}
+
+#[test]
+fn object_type_change_uses_replace() {
+    let before = json!({"a": {"x": 1}, "b": {"y": 2}, "c": 3});
+    let after = json!({"a": "flat", "b": [1, 2], "c": {"z": 3}});
+    let transform = assert_roundtrip(&before, &after);
+    assert_eq!(
+        transform,
+        json!({"a": {"@jdt.replace": "flat"}, "b": {"@jdt.replace": [[1, 2]]}, "c": {"z": 3}})
+    );
+}
+
+#[test]
+fn error_non_object_documents() {
+    let err = diff_to_transform(&json!([1]), &json!({})).unwrap_err();
+    assert!(matches!(err, JdtError::SourceNotObject));
+}
+
+#[test]
+fn error_reserved_keys() {
+    let err = diff_to_transform(&json!({}), &json!({"a": [{"@jdt.remove": "b"}]})).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "key at \"/a/0/@jdt.remove\" is reserved for JDT syntax"
+    );
+    let err = diff_to_transform(&json!({"@jdt.x": 1}), &json!({})).unwrap_err();
+    assert!(matches!(err, JdtError::ReservedKey(path) if path == "/@jdt.x"));
+    // Other names starting with `@` are plain keys.
+    assert_roundtrip(&json!({"@a": 1}), &json!({"@a": 2, "@jdt": {"x": 1}}));
+}
+
+// ── Property tests ──────────────────────────────────────────────────────
+
+proptest! {
+    #[test]
+    fn apply_diff_reproduces_after(
+        before in json_document(),
+        after in json_document(),
+    ) {
+        let transform = diff_to_transform(&before, &after).unwrap();
+        prop_assert_eq!(apply(&before, &transform).unwrap(), after);
+    }
+
+    #[test]
+    fn diff_of_identical_documents_is_empty(doc in json_document()) {
+        prop_assert_eq!(diff_to_transform(&doc, &doc).unwrap(), json!({}));
+    }
+}
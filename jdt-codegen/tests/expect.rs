@@ -0,0 +1,111 @@
+use jdt_codegen::{apply, ApplyOptions, JdtError};
+use serde_json::{json, Value};
+
+fn source() -> Value {
+    json!({
+        "Databases": [
+            {"Name": "main", "Primary": true, "Host": "localhost"},
+            {"Name": "replica", "Primary": false, "Host": "localhost"}
+        ]
+    })
+}
+
+fn replace_hosts(filter: &str, expect: Value) -> Value {
+    json!({
+        "@jdt.replace": {
+            "@jdt.path": format!("$.Databases[{filter}].Host"),
+            "@jdt.expect": expect,
+            "@jdt.value": "db.example.com"
+        }
+    })
+}
+
+#[test]
+fn exact_count_is_accepted() {
+    let result = apply(&source(), &replace_hosts("?(@.Primary == true)", json!(1))).unwrap();
+    assert_eq!(result["Databases"][0]["Host"], "db.example.com");
+    assert_eq!(result["Databases"][1]["Host"], "localhost");
+}
+
+#[test]
+fn ranges_bound_the_count() {
+    for expect in ["2", "1..", "0..2", "..2", "2..2", " 1 .. 3 "] {
+        assert!(
+            apply(&source(), &replace_hosts("0,1", json!(expect))).is_ok(),
+            "{expect}"
+        );
+    }
+    for expect in ["3", "3..", "0..1", "..1"] {
+        let err = apply(&source(), &replace_hosts("0,1", json!(expect))).unwrap_err();
+        assert!(
+            matches!(err, JdtError::UnexpectedMatchCount { actual: 2, .. }),
+            "{expect}"
+        );
+    }
+}
+
+#[test]
+fn error_reports_selector_expected_and_actual() {
+    let transform = replace_hosts("?(@.Primary == true)", json!("2.."));
+    let err = apply(&source(), &transform).unwrap_err();
+    assert!(matches!(
+        &err,
+        JdtError::UnexpectedMatchCount { selector, expected, actual: 1 }
+            if selector == "$.Databases[?(@.Primary == true)].Host" && expected == "2.."
+    ));
+    assert_eq!(
+        err.to_string(),
+        "selector $.Databases[?(@.Primary == true)].Host matched 1 nodes, expected 2.."
+    );
+}
+
+#[test]
+fn every_selector_verb_checks_the_count() {
+    let source = json!({"a": {"b": 1, "c": 2}});
+    let selector = |verb: &str, value: Value| {
+        let mut call = json!({"@jdt.path": "$.a.missing", "@jdt.expect": 1});
+        if !value.is_null() {
+            call["@jdt.value"] = value;
+        }
+        json!({ verb: call })
+    };
+    for transform in [
+        selector("@jdt.remove", Value::Null),
+        selector("@jdt.replace", json!(1)),
+        selector("@jdt.rename", json!("x")),
+        selector("@jdt.merge", json!({"x": 1})),
+    ] {
+        let err = apply(&source, &transform).unwrap_err();
+        assert!(
+            matches!(err, JdtError::UnexpectedMatchCount { actual: 0, .. }),
+            "{transform}"
+        );
+    }
+}
+
+#[test]
+fn expect_overrides_strict_mode() {
+    let strict = ApplyOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let optional = replace_hosts("?(@.Name == \"backup\")", json!("0.."));
+    assert_eq!(strict.apply(&source(), &optional).unwrap(), source());
+}
+
+#[test]
+fn error_invalid_expect() {
+    for expect in [
+        json!(-1),
+        json!(1.5),
+        json!("one"),
+        json!("3..1"),
+        json!(true),
+    ] {
+        let err = apply(&source(), &replace_hosts("0", expect.clone())).unwrap_err();
+        assert!(
+            matches!(err, JdtError::InvalidAttributeValue("@jdt.expect", _)),
+            "{expect}"
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use jdt_codegen::{apply, apply_in_place, diff_to_transform, ApplyOptions, JdtError};
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+mod common;
+use common::json_document;
+
+/// Apply in place and check a failure leaves the document untouched.
+fn assert_rolls_back(opts: &ApplyOptions, source: &Value, transform: &Value) -> JdtError {
+    let mut document = source.clone();
+    let err = opts.apply_in_place(&mut document, transform).unwrap_err();
+    assert_eq!(document, *source);
+    err
+}
+
+#[test]
+fn matches_apply() {
+    let source = json!({"a": {"b": [1]}, "c": 1, "d": {"e": 1}});
+    let transform = json!({
+        "a": {"b": [2], "@jdt.rename": {"b": "list"}},
+        "@jdt.remove": "c",
+        "@jdt.merge": {"@jdt.path": "$.d", "@jdt.value": {"f": 2}}
+    });
+    let mut document = source.clone();
+    apply_in_place(&mut document, &transform).unwrap();
+    assert_eq!(document, apply(&source, &transform).unwrap());
+}
+
+#[test]
+fn failing_verb_rolls_back_earlier_changes() {
+    let source = json!({"a": {"x": 1, "list": [1]}, "b": 1, "c": 2, "keep": {"k": true}});
+    let transform = json!({
+        "a": {"x": 2, "list": [2], "y": {"deep": true}},
+        "@jdt.remove": "keep",
+        "@jdt.replace": {"@jdt.path": "$.b", "@jdt.value": {"new": 1}},
+        "@jdt.rename": {"c": "b", "@jdt.onCollision": "error"}
+    });
+    let err = assert_rolls_back(&ApplyOptions::default(), &source, &transform);
+    assert!(matches!(err, JdtError::RenameCollision(_)));
+}
+
+#[test]
+fn failing_nested_merge_rolls_back_renames_and_removals() {
+    let source = json!({"Items": [{"id": 1, "old": 1}, {"id": 2, "old": 2}], "gone": 1});
+    let transform = json!({
+        "@jdt.remove": "gone",
+        "@jdt.rename": {"@jdt.path": "$.Items[0,1].old", "@jdt.value": "new"},
+        "@jdt.merge": [
+            {"@jdt.path": "$.Items[1]", "@jdt.value": {"x": [1]}},
+            {"@jdt.path": "$.Items[0]", "@jdt.value": {"@jdt.remove": 5}}
+        ]
+    });
+    let err = assert_rolls_back(&ApplyOptions::default(), &source, &transform);
+    assert!(matches!(err, JdtError::TransformNotObject));
+}
+
+#[test]
+fn failing_default_key_rolls_back_keyed_merge() {
+    let source = json!({"list": [{"id": 1}, {"id": 2}, {"id": 3}], "other": {"v": 1}});
+    let transform = json!({
+        "list": {"@jdt.mergeKey": "id", "@jdt.removeUnmatched": true,
+                 "@jdt.value": [{"id": 2, "v": 2}, {"id": 4}]},
+        "other": {"v": 2},
+        "z": {"@jdt.arrayMerge": "bogus", "@jdt.value": [1]}
+    });
+    let err = assert_rolls_back(&ApplyOptions::default(), &source, &transform);
+    assert!(matches!(err, JdtError::InvalidAttributeValue(..)));
+}
+
+#[test]
+fn error_source_not_object_leaves_document() {
+    let err = assert_rolls_back(&ApplyOptions::default(), &json!([1]), &json!({"a": 1}));
+    assert!(matches!(err, JdtError::SourceNotObject));
+}
+
+proptest! {
+    #[test]
+    fn failure_after_any_changes_restores_source(source in json_document(), target in json_document()) {
+        // Renaming the root fails after recursion, removals and replacements have run.
+        let mut transform = diff_to_transform(&source, &target).unwrap();
+        transform["@jdt.rename"] = json!({"@jdt.path": "$", "@jdt.value": "x"});
+        let mut document = source.clone();
+        let err = apply_in_place(&mut document, &transform).unwrap_err();
+        prop_assert!(matches!(err, JdtError::RenameNotProperty));
+        prop_assert_eq!(document, source);
+    }
+}
@@ -0,0 +1,169 @@
+use jdt_codegen::{
+    apply_json_patch, apply_with_inverse, diff_to_transform, ApplyOptions, ArrayMerge, JdtError,
+    RenameCollision,
+};
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+mod common;
+use common::json_document;
+
+/// Apply `transform` and check the inverse restores `source`.
+fn assert_restores(opts: &ApplyOptions, source: &Value, transform: &Value) -> Value {
+    let (result, inverse) = opts.apply_with_inverse(source, transform).unwrap();
+    assert_eq!(result, opts.apply(source, transform).unwrap());
+    assert_eq!(
+        apply_json_patch(&result, &inverse).unwrap(),
+        *source,
+        "inverse {inverse}"
+    );
+    inverse
+}
+
+// ── Inverse transforms ──────────────────────────────────────────────────
+
+#[test]
+fn restores_overwritten_and_added_values() {
+    let source = json!({"a": 1, "b": {"c": [1, 2]}});
+    let inverse = assert_restores(
+        &ApplyOptions::default(),
+        &source,
+        &json!({"a": 2, "b": {"c": [3], "d": true}}),
+    );
+    assert_eq!(
+        inverse,
+        json!([
+            {"op": "replace", "path": "/a", "value": 1},
+            {"op": "remove", "path": "/b/d"},
+            {"op": "remove", "path": "/b/c/2"}
+        ])
+    );
+}
+
+#[test]
+fn restores_removed_values_and_array_elements() {
+    let source = json!({"a": {"deep": [1, {"x": 1}]}, "items": [1, 2, 3, 4]});
+    let transform = json!({
+        "@jdt.remove": ["a", {"@jdt.path": "$.items[1,3]"}]
+    });
+    assert_restores(&ApplyOptions::default(), &source, &transform);
+}
+
+#[test]
+fn restores_renamed_and_overwritten_keys() {
+    let source = json!({"old": 1, "new": 2, "Nested": {"x": {"y": 1}}});
+    let transform = json!({
+        "@jdt.rename": [{"old": "new"}, {"@jdt.path": "$.Nested.x", "@jdt.value": "z"}]
+    });
+    let inverse = assert_restores(&ApplyOptions::default(), &source, &transform);
+    assert_eq!(
+        inverse,
+        json!([
+            {"op": "move", "from": "/Nested/z", "path": "/Nested/x"},
+            {"op": "move", "from": "/new", "path": "/old"},
+            {"op": "add", "path": "/new", "value": 2}
+        ])
+    );
+}
+
+#[test]
+fn restores_merged_rename_collision() {
+    let opts = ApplyOptions {
+        rename_collision: RenameCollision::Merge,
+        ..Default::default()
+    };
+    let source = json!({"a": {"x": 1, "list": [1]}, "b": {"y": 2, "list": [0]}});
+    assert_restores(&opts, &source, &json!({"@jdt.rename": {"a": "b"}}));
+}
+
+#[test]
+fn restores_replaced_nodes() {
+    let source = json!({"A": {"B": {"C": 1}}, "list": [1, 2]});
+    let transform = json!({
+        "A": {"B": {"@jdt.replace": 5}},
+        "@jdt.replace": {"@jdt.path": "$.list[0]", "@jdt.value": {"new": true}}
+    });
+    assert_restores(&ApplyOptions::default(), &source, &transform);
+}
+
+#[test]
+fn restores_array_policies() {
+    let source = json!({
+        "keyed": [{"id": 1, "v": 1}, {"id": 2}, {"id": 3}],
+        "positional": [{"a": 1}, 2],
+        "replaced": [1, 2]
+    });
+    let transform = json!({
+        "keyed": {"@jdt.mergeKey": "id", "@jdt.removeUnmatched": true,
+                  "@jdt.value": [{"id": 3, "v": 3}, {"id": 4}]},
+        "positional": {"@jdt.arrayMerge": "positional", "@jdt.value": [{"b": 2}, 3, 4]},
+        "replaced": {"@jdt.arrayMerge": "replace", "@jdt.value": [9]}
+    });
+    assert_restores(&ApplyOptions::default(), &source, &transform);
+}
+
+#[test]
+fn restores_merge_verb_changes() {
+    let source = json!({"Items": [{"env": "prod", "on": false}, {"env": "dev"}]});
+    let transform = json!({
+        "@jdt.merge": {"@jdt.path": "$.Items[?(@.env == \"prod\")]", "@jdt.value": {"on": true, "x": [1]}}
+    });
+    let opts = ApplyOptions {
+        array_merge: ArrayMerge::Union,
+        ..Default::default()
+    };
+    assert_restores(&opts, &source, &transform);
+}
+
+#[test]
+fn free_function_uses_default_options() {
+    let (result, inverse) = apply_with_inverse(&json!({"a": [1]}), &json!({"a": [2]})).unwrap();
+    assert_eq!(result, json!({"a": [1, 2]}));
+    assert_eq!(inverse, json!([{"op": "remove", "path": "/a/1"}]));
+}
+
+// ── apply_json_patch ────────────────────────────────────────────────────
+
+#[test]
+fn json_patch_supports_all_operations() {
+    let doc = json!({"a": {"b": 1}, "list": [1, 3]});
+    let patch = json!([
+        {"op": "test", "path": "/a/b", "value": 1},
+        {"op": "add", "path": "/list/1", "value": 2},
+        {"op": "add", "path": "/list/-", "value": 4},
+        {"op": "copy", "from": "/a", "path": "/copy"},
+        {"op": "move", "from": "/a/b", "path": "/moved"},
+        {"op": "replace", "path": "/copy/b", "value": 5},
+        {"op": "remove", "path": "/a"}
+    ]);
+    assert_eq!(
+        apply_json_patch(&doc, &patch).unwrap(),
+        json!({"list": [1, 2, 3, 4], "copy": {"b": 5}, "moved": 1})
+    );
+}
+
+#[test]
+fn error_json_patch_failed_test_or_missing_path() {
+    let doc = json!({"a": 1, "list": []});
+    for patch in [
+        json!([{"op": "test", "path": "/a", "value": 2}]),
+        json!([{"op": "remove", "path": "/missing"}]),
+        json!([{"op": "replace", "path": "/list/0", "value": 1}]),
+        json!([{"op": "add", "path": "/list/01", "value": 1}]),
+        json!([{"op": "add", "path": "/missing/x", "value": 1}]),
+    ] {
+        let err = apply_json_patch(&doc, &patch).unwrap_err();
+        assert!(matches!(err, JdtError::InvalidPatch(_)), "{patch}");
+    }
+}
+
+proptest! {
+    #[test]
+    fn inverse_restores_source(source in json_document(), target in json_document(), other in json_document()) {
+        let opts = ApplyOptions::default();
+        for transform in [diff_to_transform(&source, &target).unwrap(), other] {
+            let (result, inverse) = opts.apply_with_inverse(&source, &transform).unwrap();
+            prop_assert_eq!(apply_json_patch(&result, &inverse).unwrap(), source.clone());
+        }
+    }
+}
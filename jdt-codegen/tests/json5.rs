@@ -0,0 +1,125 @@
+use jdt_codegen::{apply, parse_json5, ApplyOptions, InputFormat, JdtError};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+/// `(line, column, message)` of a JSON5 syntax error.
+fn error_at(text: &str) -> (usize, usize, String) {
+    match parse_json5(text) {
+        Err(JdtError::Syntax {
+            line,
+            column,
+            message,
+        }) => (line, column, message),
+        other => panic!("expected a syntax error for {text:?}, got {other:?}"),
+    }
+}
+
+#[test]
+fn parses_the_json5_example() {
+    let text = r#"{
+  // comments
+  unquoted: 'and you can quote me on that',
+  singleQuotes: 'I can use "double quotes" here',
+  lineBreaks: "Look, Mom! \
+No \\n's!",
+  hexadecimal: 0xdecaf,
+  leadingDecimalPoint: .8675309, andTrailing: 8675309.,
+  positiveSign: +1,
+  trailingComma: 'in objects', andIn: ['arrays',],
+  "backwardsCompatible": "with JSON",
+}"#;
+    assert_eq!(
+        parse_json5(text).unwrap(),
+        json!({
+            "unquoted": "and you can quote me on that",
+            "singleQuotes": "I can use \"double quotes\" here",
+            "lineBreaks": "Look, Mom! No \\n's!",
+            "hexadecimal": 912559,
+            "leadingDecimalPoint": 0.8675309,
+            "andTrailing": 8675309.0,
+            "positiveSign": 1,
+            "trailingComma": "in objects",
+            "andIn": ["arrays"],
+            "backwardsCompatible": "with JSON"
+        })
+    );
+}
+
+#[test]
+fn decodes_escapes_and_identifiers() {
+    let text = r"{ $id_1: '\x41é😀\v\0\'\q', ab: -0x10, ünï: 1e2 }";
+    assert_eq!(
+        parse_json5(text).unwrap(),
+        json!({"$id_1": "Aé😀\u{b}\0'q", "ab": -16, "ünï": 100.0})
+    );
+}
+
+#[test]
+fn accepts_json5_whitespace() {
+    let text = "\u{feff}{\u{a0}a\u{2028}:\u{3000}1\u{b}}";
+    assert_eq!(parse_json5(text).unwrap(), json!({"a": 1}));
+}
+
+#[test]
+fn errors_point_at_the_offending_token() {
+    assert_eq!(
+        error_at("{\n  a: Infinity\n}"),
+        (
+            2,
+            6,
+            "Infinity and NaN cannot be represented in JSON".to_string()
+        )
+    );
+    assert_eq!(error_at("[1, -NaN]").1, 5);
+    assert_eq!(
+        error_at("{a: 'abc}"),
+        (1, 10, "unterminated string".to_string())
+    );
+    assert_eq!(
+        error_at("'x\ny'"),
+        (1, 3, "line break in string".to_string())
+    );
+    assert_eq!(
+        error_at("['ok', '\\uD800']"),
+        (1, 9, "unpaired surrogate in escape".to_string())
+    );
+    assert_eq!(error_at("'\\1'").1, 2);
+    assert_eq!(error_at("'\\xZ1'").1, 2);
+    assert_eq!(
+        error_at("[007]"),
+        (1, 2, "leading zero in number".to_string())
+    );
+    assert_eq!(error_at("0x1_0000_0000_0000_0000").1, 4);
+    assert_eq!(
+        error_at("0x10000000000000000"),
+        (1, 1, "number out of range".to_string())
+    );
+    assert_eq!(error_at("{a b}"), (1, 4, "expected ':'".to_string()));
+    assert_eq!(error_at("{1a: 0}"), (1, 2, "expected a key".to_string()));
+    assert_eq!(error_at("[.]").1, 3);
+}
+
+#[test]
+fn transforms_can_be_authored_in_json5() {
+    let transform = parse_json5("{ port: 8080, '@jdt.remove': 'debug', }").unwrap();
+    let source = json!({"port": 80, "debug": true});
+    assert_eq!(apply(&source, &transform).unwrap(), json!({"port": 8080}));
+}
+
+#[test]
+fn text_mode_keeps_json5_syntax() {
+    let opts = ApplyOptions {
+        input_format: InputFormat::Json5,
+        ..Default::default()
+    };
+    let source = "{\n  // ports\n  port: 0x50,\n  name: 'app',\n  tags: ['a', 'b',],\n}\n";
+    let transform = json!({
+        "name": "api",
+        "tags": ["c"],
+        "@jdt.rename": {"port": "Port"}
+    });
+    assert_eq!(
+        opts.apply_text(source, &transform).unwrap(),
+        "{\n  // ports\n  \"Port\": 0x50,\n  name: \"api\",\n  tags: ['a', 'b', \"c\",],\n}\n"
+    );
+}
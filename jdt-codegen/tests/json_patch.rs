@@ -0,0 +1,172 @@
+use jdt_codegen::{
+    apply, apply_json_patch, apply_with_patch, ApplyOptions, ArrayMerge, RenameCollision,
+};
+use serde_json::{json, Value};
+
+/// Apply with a patch and check the patch reproduces the result.
+fn patched(opts: &ApplyOptions, source: &Value, transform: &Value) -> Value {
+    let (result, patch) = opts.apply_with_patch(source, transform).unwrap();
+    assert_eq!(result, opts.apply(source, transform).unwrap());
+    assert_eq!(
+        apply_json_patch(source, &patch).unwrap(),
+        result,
+        "patch {patch}"
+    );
+    patch
+}
+
+// ── Default transformation ──────────────────────────────────────────────
+
+#[test]
+fn default_merge_adds_and_replaces() {
+    let source = json!({"A": {"x": 1}, "b": 2});
+    let transform = json!({"A": {"y": 2}, "b": 3});
+    let patch = patched(&ApplyOptions::default(), &source, &transform);
+    assert_eq!(
+        patch,
+        json!([
+            {"op": "add", "path": "/A/y", "value": 2},
+            {"op": "replace", "path": "/b", "value": 3}
+        ])
+    );
+}
+
+#[test]
+fn appended_elements_are_added_by_index() {
+    let source = json!({"list": [1]});
+    let patch = patched(&ApplyOptions::default(), &source, &json!({"list": [2, 3]}));
+    assert_eq!(
+        patch,
+        json!([
+            {"op": "add", "path": "/list/1", "value": 2},
+            {"op": "add", "path": "/list/2", "value": 3}
+        ])
+    );
+}
+
+#[test]
+fn unchanged_document_gives_empty_patch() {
+    let (_, patch) = apply_with_patch(&json!({"a": 1}), &json!({})).unwrap();
+    assert_eq!(patch, json!([]));
+}
+
+#[test]
+fn transform_without_effect_gives_empty_patch() {
+    let source = json!({"a": 1, "b": {"c": [1, 2]}, "k": [{"id": 1, "N": "y"}]});
+    let transform = json!({
+        "a": 1,
+        "b": {"c": {"@jdt.arrayMerge": "replace", "@jdt.value": [1, 2]}},
+        "k": {"@jdt.mergeKey": "id", "@jdt.value": [{"id": 1, "N": "y"}]},
+        "@jdt.replace": {"@jdt.path": "$.a", "@jdt.value": 1}
+    });
+    assert_eq!(
+        patched(&ApplyOptions::default(), &source, &transform),
+        json!([])
+    );
+    let keyed = json!({"k": {"@jdt.mergeKey": "id", "@jdt.value": [{"id": 1, "N": "z"}]}});
+    assert_eq!(
+        patched(&ApplyOptions::default(), &source, &keyed),
+        json!([{"op": "replace", "path": "/k/0/N", "value": "z"}])
+    );
+}
+
+#[test]
+fn keys_are_escaped_as_json_pointers() {
+    let source = json!({"a/b": {"c~d": 1}});
+    let patch = patched(
+        &ApplyOptions::default(),
+        &source,
+        &json!({"a/b": {"c~d": 2}}),
+    );
+    assert_eq!(patch[0]["path"], "/a~1b/c~0d");
+}
+
+// ── Verbs ───────────────────────────────────────────────────────────────
+
+#[test]
+fn selector_remove_records_each_path() {
+    let source = json!({"items": [1, 2, 3], "keep": true});
+    let transform = json!({"@jdt.remove": {"@jdt.path": "$.items[0,2]"}});
+    let patch = patched(&ApplyOptions::default(), &source, &transform);
+    assert_eq!(
+        patch,
+        json!([
+            {"op": "remove", "path": "/items/2"},
+            {"op": "remove", "path": "/items/0"}
+        ])
+    );
+}
+
+#[test]
+fn replace_verb_records_replace() {
+    let source = json!({"Db": {"Host": "localhost"}});
+    let transform = json!({"Db": {"@jdt.replace": {"@jdt.path": "$.Host", "@jdt.value": "prod"}}});
+    let patch = patched(&ApplyOptions::default(), &source, &transform);
+    assert_eq!(
+        patch,
+        json!([{"op": "replace", "path": "/Db/Host", "value": "prod"}])
+    );
+}
+
+#[test]
+fn rename_records_move() {
+    let source = json!({"Nested": {"old": 1, "other": 2}});
+    let transform = json!({"@jdt.rename": {"@jdt.path": "$.Nested.old", "@jdt.value": "new"}});
+    let patch = patched(&ApplyOptions::default(), &source, &transform);
+    assert_eq!(
+        patch,
+        json!([{"op": "move", "from": "/Nested/old", "path": "/Nested/new"}])
+    );
+}
+
+#[test]
+fn rename_merge_collision_records_remove_and_replace() {
+    let opts = ApplyOptions {
+        rename_collision: RenameCollision::Merge,
+        ..Default::default()
+    };
+    let source = json!({"a": {"x": 1}, "b": {"y": 2}});
+    let patch = patched(&opts, &source, &json!({"@jdt.rename": {"a": "b"}}));
+    assert_eq!(patch.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn merge_verb_records_nested_changes() {
+    let source = json!({"Items": [{"id": 1}, {"id": 2}]});
+    let transform = json!({
+        "@jdt.merge": {"@jdt.path": "$.Items[1]", "@jdt.value": {"on": true}}
+    });
+    let patch = patched(&ApplyOptions::default(), &source, &transform);
+    assert_eq!(
+        patch,
+        json!([{"op": "add", "path": "/Items/1/on", "value": true}])
+    );
+}
+
+#[test]
+fn keyed_merge_records_element_changes() {
+    let opts = ApplyOptions {
+        array_merge: ArrayMerge::Keyed {
+            key: "name".to_string(),
+            remove_unmatched: true,
+        },
+        ..Default::default()
+    };
+    let source = json!({"list": [{"name": "a", "v": 1}, {"name": "b"}, {"name": "c"}]});
+    let transform = json!({"list": [{"name": "c", "v": 3}, {"name": "d"}]});
+    patched(&opts, &source, &transform);
+}
+
+#[test]
+fn layered_operations_replay_in_order() {
+    let source = json!({"a": {"x": [1, 2]}, "b": 1, "c": {"d": 1}});
+    let transform = json!({
+        "a": {"x": [3], "@jdt.rename": {"x": "y"}},
+        "@jdt.remove": "b",
+        "@jdt.replace": {"@jdt.path": "$.c.d", "@jdt.value": {"deep": true}},
+        "@jdt.merge": {"@jdt.path": "$.c", "@jdt.value": {"e": 2}},
+        "f": null
+    });
+    patched(&ApplyOptions::default(), &source, &transform);
+    assert!(apply(&source, &transform).is_ok());
+}
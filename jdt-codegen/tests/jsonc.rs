@@ -0,0 +1,149 @@
+use jdt_codegen::{apply, apply_text, parse_jsonc, ApplyOptions, InputFormat, JdtError};
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+
+fn jsonc() -> ApplyOptions {
+    ApplyOptions {
+        input_format: InputFormat::Jsonc,
+        ..Default::default()
+    }
+}
+
+/// Apply to text, checking the result reads back as what `apply` produces.
+fn transformed(source: &str, transform: &Value) -> String {
+    let out = jsonc().apply_text(source, transform).unwrap();
+    let expected = apply(&parse_jsonc(source).unwrap(), transform).unwrap();
+    assert_eq!(parse_jsonc(&out).unwrap(), expected, "{out}");
+    out
+}
+
+#[test]
+fn parses_comments_and_trailing_commas() {
+    let text = "\u{feff}// settings\n{\n  /* block\n     comment */\n  \"a\": [1, 2,], // two\n  \"b\": {\"c\": \"// not a comment\",},\n}\n";
+    assert_eq!(
+        parse_jsonc(text).unwrap(),
+        json!({"a": [1, 2], "b": {"c": "// not a comment"}})
+    );
+}
+
+#[test]
+fn syntax_errors_report_line_and_column() {
+    let err = parse_jsonc("{\n  \"a\": 1 /* open").unwrap_err();
+    assert!(matches!(
+        err,
+        JdtError::Syntax {
+            line: 2,
+            column: 10,
+            ..
+        }
+    ));
+    for bad in ["[1,,]", "[,]", "{,}", "{\"a\": 1 / 2}", "// only a comment"] {
+        assert!(
+            matches!(parse_jsonc(bad), Err(JdtError::Syntax { .. })),
+            "{bad}"
+        );
+    }
+}
+
+#[test]
+fn strict_json_rejects_comments() {
+    let err = apply_text("{\"a\": 1 // one\n}", &json!({})).unwrap_err();
+    assert!(matches!(err, JdtError::Syntax { line: 1, .. }));
+    let err = apply_text("{\"a\": 1,}", &json!({})).unwrap_err();
+    assert!(matches!(err, JdtError::Syntax { .. }));
+}
+
+#[test]
+fn comments_survive_changes() {
+    let source = "{\n  // Database\n  \"Host\": \"localhost\", // dev only\n  \"Port\": 5432 /* default */\n}\n";
+    assert_eq!(
+        transformed(source, &json!({"Host": "db", "Port": 6543})),
+        "{\n  // Database\n  \"Host\": \"db\", // dev only\n  \"Port\": 6543 /* default */\n}\n"
+    );
+}
+
+#[test]
+fn appended_member_goes_after_a_trailing_comment() {
+    let source = "{\n  \"a\": 1 // one\n}";
+    assert_eq!(
+        transformed(source, &json!({"b": 2})),
+        "{\n  \"a\": 1, // one\n  \"b\": 2\n}"
+    );
+}
+
+#[test]
+fn appended_member_starts_a_line_after_a_line_comment() {
+    assert_eq!(
+        transformed("{\"a\": 1 // c\n}", &json!({"b": 2})),
+        "{\"a\": 1, // c\n  \"b\": 2\n}"
+    );
+    assert_eq!(
+        transformed(
+            "[1 /* a */ // b\n]",
+            &json!({"@jdt.merge": {"@jdt.path": "$", "@jdt.value": [2]}})
+        ),
+        "[1, /* a */ // b\n  2\n]"
+    );
+    assert_eq!(
+        transformed("{\"a\": 1 /* c */}", &json!({"b": 2})),
+        "{\"a\": 1, /* c */ \"b\": 2}"
+    );
+}
+
+#[test]
+fn removed_member_takes_its_comments() {
+    let source = "{\n  \"a\": 1, // one\n  // about b\n  \"b\": 2, // two\n  \"c\": 3\n}";
+    assert_eq!(
+        transformed(source, &json!({"@jdt.remove": "b"})),
+        "{\n  \"a\": 1, // one\n  \"c\": 3\n}"
+    );
+    assert_eq!(
+        transformed(source, &json!({"@jdt.remove": "c"})),
+        "{\n  \"a\": 1, // one\n  // about b\n  \"b\": 2 // two\n}"
+    );
+}
+
+#[test]
+fn removed_last_member_takes_its_trailing_comment() {
+    let source = "{\n  \"a\": 1, // trailing\n  \"b\": 2 // last\n}";
+    assert_eq!(
+        transformed(source, &json!({"@jdt.remove": "b"})),
+        "{\n  \"a\": 1 // trailing\n}"
+    );
+    let source = "{\"a\": 1, \"b\": 2 /* last */}";
+    assert_eq!(
+        transformed(source, &json!({"@jdt.remove": "b"})),
+        "{\"a\": 1}"
+    );
+}
+
+#[test]
+fn trailing_commas_are_kept() {
+    let source = "{\n  \"a\": [\n    1,\n  ],\n  \"b\": 2,\n}";
+    assert_eq!(
+        transformed(source, &json!({"a": [2], "c": 3})),
+        "{\n  \"a\": [\n    1,\n    2,\n  ],\n  \"b\": 2,\n  \"c\": 3,\n}"
+    );
+    assert_eq!(
+        transformed(source, &json!({"@jdt.remove": "b"})),
+        "{\n  \"a\": [\n    1,\n  ],\n}"
+    );
+}
+
+#[test]
+fn comment_in_empty_object_stays_first() {
+    let source = "{\n  \"a\": { // none yet\n  }\n}";
+    assert_eq!(
+        transformed(source, &json!({"a": {"x": 1}})),
+        "{\n  \"a\": { // none yet\n    \"x\": 1\n  }\n}"
+    );
+}
+
+#[test]
+fn crlf_line_endings_are_kept() {
+    let source = "{\r\n  \"a\": 1 // one\r\n}\r\n";
+    assert_eq!(
+        transformed(source, &json!({"b": {"c": true}})),
+        "{\r\n  \"a\": 1, // one\r\n  \"b\": {\r\n    \"c\": true\r\n  }\r\n}\r\n"
+    );
+}
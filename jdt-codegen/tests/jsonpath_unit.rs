@@ -112,6 +112,20 @@ fn parse_union_with_whitespace() {
     );
 }
 
+#[test]
+fn parse_quoted_names() {
+    let doc = json!({"a.b": {"it's": {"\"q\"": 1}}, "*": 2, "é": 3});
+    let jp = JsonPath::parse(r#"$['a.b']["it's"]["\"q\""]"#).unwrap();
+    let expected = ["a.b", "it's", "\"q\""].map(|k| PathItem::Key(k.into()));
+    assert_eq!(jp.select_paths(&doc), vec![expected.to_vec()]);
+    let jp = JsonPath::parse(r"$['a\u002eb']['it\'s']").unwrap();
+    assert_eq!(jp.select_paths(&doc), vec![expected[..2].to_vec()]);
+    let jp = JsonPath::parse(r"$['*']").unwrap();
+    assert_eq!(jp.select_paths(&doc), vec![vec![PathItem::Key("*".into())]]);
+    let jp = JsonPath::parse(r"$['\u00e9']").unwrap();
+    assert_eq!(jp.select_paths(&doc), vec![vec![PathItem::Key("é".into())]]);
+}
+
 #[test]
 fn parse_filter_exists() {
     let jp = JsonPath::parse("$.items[?(@.active)]").unwrap();
@@ -224,6 +238,23 @@ fn parse_filter_on_object() {
     assert!(!keys.contains(&"b"));
 }
 
+#[test]
+fn select_ignoring_case_returns_source_spelling() {
+    let jp = JsonPath::parse(r#"$.ITEMS[?(@.Name == "b")]"#).unwrap();
+    let data = json!({"items": [{"name": "a"}, {"name": "b"}]});
+    assert!(jp.select_paths(&data).is_empty());
+    assert_eq!(
+        jp.select_paths_ignore_case(&data),
+        vec![vec![PathItem::Key("items".into()), PathItem::Index(1)]]
+    );
+    // An exact spelling is preferred over other cases.
+    let jp = JsonPath::parse("$.key").unwrap();
+    assert_eq!(
+        jp.select_paths_ignore_case(&json!({"KEY": 1, "key": 2})),
+        vec![vec![PathItem::Key("key".into())]]
+    );
+}
+
 #[test]
 fn select_missing_child_returns_empty() {
     let jp = JsonPath::parse("$.missing").unwrap();
@@ -290,6 +321,23 @@ fn error_missing_close_bracket() {
     assert!(matches!(err, JsonPathError::Invalid { .. }));
 }
 
+#[test]
+fn error_quoted_name() {
+    for path in [
+        r"$['foo",
+        r"$['foo'",
+        r"$['fo\xo']",
+        r"$['\ud800']",
+        r"$['\u12']",
+        r#"$['\"']"#,
+        r#"$["\'"]"#,
+        "$['\t']",
+    ] {
+        let err = JsonPath::parse(path).unwrap_err();
+        assert!(matches!(err, JsonPathError::Invalid { .. }), "{path}");
+    }
+}
+
 #[test]
 fn error_unexpected_char() {
     let err = JsonPath::parse("$!foo").unwrap_err();
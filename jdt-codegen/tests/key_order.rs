@@ -0,0 +1,97 @@
+#![cfg(feature = "preserve_order")]
+
+use jdt_codegen::{apply, apply_in_place, apply_json_patch, ApplyOptions, RenameCollision};
+use serde_json::{json, Value};
+
+/// `Value` equality ignores key order, so compare the serialized text.
+fn assert_text(actual: &Value, expected: &str) {
+    assert_eq!(serde_json::to_string(actual).unwrap(), expected);
+}
+
+fn parse(text: &str) -> Value {
+    serde_json::from_str(text).unwrap()
+}
+
+#[test]
+fn untouched_and_replaced_keys_keep_their_place() {
+    let source = parse(r#"{"z":1,"a":{"y":1,"b":2},"m":3}"#);
+    let result = apply(&source, &parse(r#"{"m":4,"a":{"b":5}}"#)).unwrap();
+    assert_text(&result, r#"{"z":1,"a":{"y":1,"b":5},"m":4}"#);
+}
+
+#[test]
+fn added_keys_are_appended_in_transform_order() {
+    let source = parse(r#"{"z":1,"a":{"y":1}}"#);
+    let transform = parse(r#"{"q":1,"a":{"x":2,"b":3},"c":4}"#);
+    let result = apply(&source, &transform).unwrap();
+    assert_text(&result, r#"{"z":1,"a":{"y":1,"x":2,"b":3},"q":1,"c":4}"#);
+}
+
+#[test]
+fn removals_keep_sibling_order() {
+    let source = parse(r#"{"z":1,"y":2,"x":3,"w":{"c":1,"b":2,"a":3}}"#);
+    let transform = json!({"@jdt.remove": ["y", {"@jdt.path": "$.w.b"}]});
+    let result = apply(&source, &transform).unwrap();
+    assert_text(&result, r#"{"z":1,"x":3,"w":{"c":1,"a":3}}"#);
+}
+
+#[test]
+fn renamed_keys_stay_in_place() {
+    let source = parse(r#"{"c":1,"b":2,"a":3}"#);
+    let transform = json!({"@jdt.rename": [{"b": "x"}, {"@jdt.path": "$.a", "@jdt.value": "y"}]});
+    assert_text(
+        &apply(&source, &transform).unwrap(),
+        r#"{"c":1,"x":2,"y":3}"#,
+    );
+}
+
+#[test]
+fn rename_collisions_keep_a_single_key() {
+    let source = parse(r#"{"a":{"x":1},"b":2,"c":{"y":2}}"#);
+    let rename = json!({"@jdt.rename": {"c": "a"}});
+    // Overwrite: the renamed value takes the old key's place.
+    assert_text(&apply(&source, &rename).unwrap(), r#"{"b":2,"a":{"y":2}}"#);
+    // Merge: the existing key keeps its place.
+    let opts = ApplyOptions {
+        rename_collision: RenameCollision::Merge,
+        ..Default::default()
+    };
+    assert_text(
+        &opts.apply(&source, &rename).unwrap(),
+        r#"{"a":{"x":1,"y":2},"b":2}"#,
+    );
+}
+
+#[test]
+fn merge_patch_removal_keeps_order() {
+    let opts = ApplyOptions {
+        merge_patch: true,
+        ..Default::default()
+    };
+    let source = parse(r#"{"z":1,"y":2,"x":3}"#);
+    let result = opts.apply(&source, &parse(r#"{"y":null,"w":4}"#)).unwrap();
+    assert_text(&result, r#"{"z":1,"x":3,"w":4}"#);
+}
+
+#[test]
+fn rollback_restores_key_order() {
+    let text = r#"{"z":1,"y":{"c":1,"b":2},"x":3,"w":4}"#;
+    let mut document = parse(text);
+    let transform = json!({
+        "y": {"@jdt.rename": {"c": "a"}, "@jdt.remove": "b"},
+        "@jdt.remove": "x",
+        "@jdt.rename": [{"z": "w"}, {"@jdt.path": "$", "@jdt.value": "root"}]
+    });
+    assert!(apply_in_place(&mut document, &transform).is_err());
+    assert_text(&document, text);
+}
+
+#[test]
+fn json_patch_remove_keeps_order() {
+    let source = parse(r#"{"z":1,"y":2,"x":3}"#);
+    let patch = json!([{"op": "remove", "path": "/y"}]);
+    assert_text(
+        &apply_json_patch(&source, &patch).unwrap(),
+        r#"{"z":1,"x":3}"#,
+    );
+}
@@ -0,0 +1,223 @@
+use jdt_codegen::{
+    apply, apply_text, diff_to_transform, strip_bom, ApplyOptions, ArrayMerge, JdtError,
+};
+use pretty_assertions::assert_eq;
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+mod common;
+use common::json_document;
+
+/// Apply to text, checking the result holds what `apply` produces.
+fn transformed(source: &str, transform: &Value) -> String {
+    let out = apply_text(source, transform).unwrap();
+    let expected = apply(&serde_json::from_str(strip_bom(source)).unwrap(), transform).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Value>(strip_bom(&out)).unwrap(),
+        expected
+    );
+    out
+}
+
+#[test]
+fn untouched_text_is_kept_byte_for_byte() {
+    let source = "\u{feff}  {\n\t\"a\" :1.50,\n\n\t\"b\":[ 1e3 ,2 ],\"c\": {  }\n}\n\n";
+    assert_eq!(transformed(source, &json!({})), source);
+    assert_eq!(transformed(source, &json!({"a": 1.5, "c": {}})), source);
+}
+
+#[test]
+fn changed_scalar_rewrites_only_its_token() {
+    let source = "{\n    \"Port\" :  80.0 ,\n\n    \"Host\":\"localhost\"   \n}";
+    let out = transformed(source, &json!({"Host": "prod"}));
+    assert_eq!(
+        out,
+        "{\n    \"Port\" :  80.0 ,\n\n    \"Host\":\"prod\"   \n}"
+    );
+}
+
+#[test]
+fn added_key_follows_sibling_formatting() {
+    let source = "{\n\t\"a\": 1,\n\t\"b\": 2\n}\n";
+    let out = transformed(source, &json!({"c": {"d": [1]}}));
+    assert_eq!(
+        out,
+        "{\n\t\"a\": 1,\n\t\"b\": 2,\n\t\"c\": {\n\t\t\"d\": [\n\t\t\t1\n\t\t]\n\t}\n}\n"
+    );
+}
+
+#[test]
+fn added_key_in_empty_object_is_indented() {
+    let source = "{\n  \"a\": {},\n  \"b\": 1\n}";
+    let out = transformed(source, &json!({"a": {"x": true}}));
+    assert_eq!(out, "{\n  \"a\": {\n    \"x\": true\n  },\n  \"b\": 1\n}");
+}
+
+#[test]
+fn compact_text_stays_compact() {
+    let source = r#"{"a":1,"b":{"c":[1,2]}}"#;
+    let out = transformed(source, &json!({"b": {"c": [3], "d": {"e": null}}}));
+    assert_eq!(out, r#"{"a":1,"b":{"c":[1,2,3],"d":{"e":null}}}"#);
+}
+
+#[test]
+fn single_line_text_keeps_its_spacing() {
+    let out = transformed(r#"{"a": 1}"#, &json!({"b": {"d": 1, "e": [1, 2]}}));
+    assert_eq!(out, r#"{"a": 1, "b": {"d": 1, "e": [1, 2]}}"#);
+    let out = transformed(r#"{"a" : 1,  "b" : 2}"#, &json!({"c": {"d": 1}}));
+    assert_eq!(out, r#"{"a" : 1,  "b" : 2,  "c" : {"d" : 1}}"#);
+    let source = r#"{"k": [{"id": 1, "N": "y"}]}"#;
+    let transform = json!({"k": {"@jdt.mergeKey": "id", "@jdt.value": [{"id": 1, "q": 1}]}});
+    assert_eq!(
+        transformed(source, &transform),
+        r#"{"k": [{"id": 1, "N": "y", "q": 1}]}"#
+    );
+}
+
+#[test]
+fn removed_members_take_their_commas() {
+    let source = "{\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": 3\n}";
+    assert_eq!(
+        transformed(source, &json!({"@jdt.remove": "b"})),
+        "{\n  \"a\": 1,\n  \"c\": 3\n}"
+    );
+    assert_eq!(
+        transformed(source, &json!({"@jdt.remove": "c"})),
+        "{\n  \"a\": 1,\n  \"b\": 2\n}"
+    );
+    assert_eq!(
+        transformed(source, &json!({"@jdt.remove": "a"})),
+        "{\n  \"b\": 2,\n  \"c\": 3\n}"
+    );
+    assert_eq!(
+        transformed(source, &json!({"@jdt.remove": ["a", "b", "c"]})),
+        "{}"
+    );
+}
+
+#[test]
+fn rename_keeps_position_and_value_text() {
+    let source = "{\n  \"old\": [ 1.0, 2 ],\n  \"z\": 0\n}";
+    let out = transformed(source, &json!({"@jdt.rename": {"old": "new"}}));
+    assert_eq!(out, "{\n  \"new\": [ 1.0, 2 ],\n  \"z\": 0\n}");
+}
+
+#[test]
+fn edits_inside_added_values_are_applied() {
+    // The second merge changes the object the first one adds.
+    let source = "{\n  \"a\": 1\n}";
+    let transform = json!({
+        "@jdt.merge": [
+            {"@jdt.path": "$", "@jdt.value": {"n": {"x": 1}}},
+            {"@jdt.path": "$.n", "@jdt.value": {"y": 2}}
+        ]
+    });
+    let out = transformed(source, &transform);
+    assert_eq!(
+        out,
+        "{\n  \"a\": 1,\n  \"n\": {\n    \"x\": 1,\n    \"y\": 2\n  }\n}"
+    );
+}
+
+#[test]
+fn options_are_honoured() {
+    let opts = ApplyOptions {
+        array_merge: ArrayMerge::Replace,
+        ..Default::default()
+    };
+    let source = "{ \"list\": [1, 2], \"n\": 1 }";
+    let out = opts.apply_text(source, &json!({"list": [3]})).unwrap();
+    assert_eq!(out, "{ \"list\": [3], \"n\": 1 }");
+}
+
+#[test]
+fn syntax_errors_report_line_and_column() {
+    let err = apply_text("{\n  \"a\": 1,\n  \"b\" 2\n}", &json!({})).unwrap_err();
+    assert!(matches!(
+        err,
+        JdtError::Syntax {
+            line: 3,
+            column: 7,
+            ..
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "invalid JSON at line 3, column 7: expected ':'"
+    );
+    for bad in [
+        "",
+        "{",
+        "[1,]",
+        "{\"a\":01}",
+        "\"\\x\"",
+        "\"\\",
+        "\"\\é\"",
+        "{} {}",
+        "tru",
+        "1e999",
+    ] {
+        assert!(
+            matches!(apply_text(bad, &json!({})), Err(JdtError::Syntax { .. })),
+            "{bad}"
+        );
+    }
+}
+
+#[test]
+fn duplicate_keys_are_syntax_errors() {
+    let err = apply_text(
+        "{\n  \"a\": 1,\n  \"a\": 2\n}",
+        &json!({"@jdt.remove": "a"}),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid JSON at line 3, column 3: duplicate key"
+    );
+    // Nested objects and arrays of objects may reuse names.
+    let out = transformed(
+        "{\"a\": {\"a\": [{\"a\": 1}, {\"a\": 2}]}}",
+        &json!({"a": {"b": 1}}),
+    );
+    assert_eq!(out, "{\"a\": {\"a\": [{\"a\": 1}, {\"a\": 2}], \"b\": 1}}");
+}
+
+#[test]
+fn deeply_nested_new_values_are_written() {
+    let mut deep = json!(1);
+    for _ in 0..200 {
+        deep = json!([deep]);
+    }
+    let transform = json!({"deep": deep});
+    let expected = apply(&json!({"a": 1}), &transform).unwrap();
+    assert_eq!(
+        apply_text("{\"a\":1}", &transform).unwrap(),
+        expected.to_string()
+    );
+    assert_eq!(
+        apply_text("{\n  \"a\": 1\n}", &transform).unwrap(),
+        serde_json::to_string_pretty(&expected).unwrap()
+    );
+}
+
+#[test]
+fn transform_errors_are_reported() {
+    let err = apply_text("[1]", &json!({"a": 1})).unwrap_err();
+    assert!(matches!(err, JdtError::SourceNotObject));
+}
+
+proptest! {
+    #[test]
+    fn text_result_matches_apply(source in json_document(), target in json_document(), pretty in any::<bool>()) {
+        let transform = diff_to_transform(&source, &target).unwrap();
+        let text = if pretty {
+            serde_json::to_string_pretty(&source).unwrap()
+        } else {
+            source.to_string()
+        };
+        let out = apply_text(&text, &transform).unwrap();
+        let parsed: Value = serde_json::from_str(&out).unwrap();
+        prop_assert_eq!(parsed, apply(&source, &transform).unwrap());
+    }
+}
@@ -0,0 +1,88 @@
+use jdt_codegen::{ApplyOptions, ArrayMerge};
+use proptest::prelude::*;
+use serde_json::json;
+
+mod common;
+use common::{json_document, merge_patch};
+
+fn merge_patch_opts() -> ApplyOptions {
+    ApplyOptions {
+        merge_patch: true,
+        ..Default::default()
+    }
+}
+
+// ── Default transformation ──────────────────────────────────────────────
+
+#[test]
+fn null_removes_key() {
+    let source = json!({"a": 1, "b": {"c": 2, "d": 3}});
+    let transform = json!({"a": null, "b": {"c": null}, "missing": null});
+    let result = merge_patch_opts().apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"b": {"d": 3}}));
+}
+
+#[test]
+fn arrays_replace() {
+    let source = json!({"list": [1, 2], "nested": {"list": [3]}});
+    let transform = json!({"list": [9], "nested": {"list": []}});
+    let result = merge_patch_opts().apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"list": [9], "nested": {"list": []}}));
+}
+
+#[test]
+fn node_array_policy_still_applies() {
+    let source = json!({"list": [1, 2]});
+    let transform = json!({"list": {"@jdt.arrayMerge": "union", "@jdt.value": [2, 3]}});
+    let result = merge_patch_opts().apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"list": [1, 2, 3]}));
+}
+
+#[test]
+fn merge_patch_overrides_option_array_policy() {
+    let opts = ApplyOptions {
+        array_merge: ArrayMerge::Append,
+        ..merge_patch_opts()
+    };
+    let result = opts
+        .apply(&json!({"list": [1]}), &json!({"list": [2]}))
+        .unwrap();
+    assert_eq!(result, json!({"list": [2]}));
+}
+
+#[test]
+fn nulls_dropped_where_nothing_to_merge_into() {
+    let source = json!({"a": 5});
+    let transform = json!({"a": {"b": null, "c": {"d": null, "e": 1}}, "new": {"x": null}});
+    let result = merge_patch_opts().apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"a": {"c": {"e": 1}}, "new": {}}));
+}
+
+// ── Verbs ───────────────────────────────────────────────────────────────
+
+#[test]
+fn verbs_keep_jdt_semantics() {
+    let source = json!({"a": {"x": 1, "list": [1]}, "b": 1});
+    let transform = json!({
+        "@jdt.remove": "b",
+        "@jdt.merge": {"@jdt.path": "$.a", "@jdt.value": {"x": null, "list": [2]}}
+    });
+    let result = merge_patch_opts().apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"a": {"x": null, "list": [1, 2]}}));
+}
+
+#[test]
+fn nested_merge_keeps_jdt_semantics() {
+    let source = json!({"a": 1});
+    let transform = json!({"@jdt.merge": {"a": null}});
+    let result = merge_patch_opts().apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"a": null}));
+}
+
+proptest! {
+    #[test]
+    fn matches_rfc_7396(target in json_document(), patch in json_document()) {
+        let result = merge_patch_opts().apply(&target, &patch).unwrap();
+        prop_assert_eq!(result, merge_patch(&target, &patch));
+    }
+}
@@ -0,0 +1,128 @@
+use jdt_codegen::{apply, apply_text, apply_with_patch, JdtError};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+fn flags() -> serde_json::Value {
+    json!([
+        {"name": "search", "enabled": true},
+        {"name": "export", "enabled": false},
+        {"name": "beta", "enabled": false}
+    ])
+}
+
+#[test]
+fn selectors_address_an_array_root() {
+    let transform = json!({
+        "@jdt.remove": {"@jdt.path": "$[?(@.name == \"beta\")]"},
+        "@jdt.replace": {"@jdt.path": "$[1].enabled", "@jdt.value": true},
+        "@jdt.rename": {"@jdt.path": "$[0].name", "@jdt.value": "id"}
+    });
+    assert_eq!(
+        apply(&flags(), &transform).unwrap(),
+        json!([
+            {"id": "search", "enabled": true},
+            {"name": "export", "enabled": true}
+        ])
+    );
+}
+
+#[test]
+fn merge_into_an_array_root() {
+    let transform = json!({
+        "@jdt.merge": [
+            {"@jdt.path": "$", "@jdt.value": [{"name": "audit", "enabled": true}]},
+            {"@jdt.path": "$[?(@.name == \"export\")]", "@jdt.value": {"owner": "ops"}}
+        ]
+    });
+    let out = apply(&flags(), &transform).unwrap();
+    assert_eq!(
+        out[1],
+        json!({"name": "export", "enabled": false, "owner": "ops"})
+    );
+    assert_eq!(out[3], json!({"name": "audit", "enabled": true}));
+
+    let keyed = json!({
+        "@jdt.merge": {
+            "@jdt.path": "$",
+            "@jdt.mergeKey": "name",
+            "@jdt.value": [{"name": "beta", "enabled": true}]
+        }
+    });
+    assert_eq!(apply(&flags(), &keyed).unwrap()[2]["enabled"], json!(true));
+}
+
+#[test]
+fn a_non_object_root_can_be_replaced_by_any_value() {
+    let transform = json!({"@jdt.replace": {"@jdt.path": "$", "@jdt.value": ["a", "b"]}});
+    assert_eq!(apply(&flags(), &transform).unwrap(), json!(["a", "b"]));
+    assert_eq!(apply(&json!(1), &transform).unwrap(), json!(["a", "b"]));
+    assert_eq!(
+        apply(&json!("v1"), &json!({"@jdt.replace": "v2"})).unwrap(),
+        json!("v2")
+    );
+    assert_eq!(
+        apply(&json!(false), &json!({"@jdt.merge": true})).unwrap(),
+        json!(true)
+    );
+    // An object root still has to stay an object.
+    assert!(matches!(
+        apply(&json!({"a": 1}), &transform),
+        Err(JdtError::RootOperationNotAllowed)
+    ));
+}
+
+#[test]
+fn the_root_cannot_be_removed() {
+    for transform in [
+        json!({"@jdt.remove": true}),
+        json!({"@jdt.remove": {"@jdt.path": "$"}}),
+    ] {
+        assert!(matches!(
+            apply(&flags(), &transform),
+            Err(JdtError::RootOperationNotAllowed)
+        ));
+    }
+}
+
+#[test]
+fn default_keys_and_names_need_an_object() {
+    for transform in [
+        json!({"key": "value"}),
+        json!({"@jdt.remove": "name"}),
+        json!({"@jdt.rename": {"name": "id"}}),
+    ] {
+        assert!(
+            matches!(apply(&flags(), &transform), Err(JdtError::SourceNotObject)),
+            "{transform}"
+        );
+    }
+    assert!(matches!(
+        apply(
+            &json!(1),
+            &json!({"@jdt.rename": {"@jdt.path": "$", "@jdt.value": "x"}})
+        ),
+        Err(JdtError::RenameNotProperty)
+    ));
+}
+
+#[test]
+fn unmatched_selectors_leave_a_scalar_root() {
+    let transform = json!({"@jdt.remove": {"@jdt.path": "$.a"}});
+    assert_eq!(apply(&json!(42), &transform).unwrap(), json!(42));
+}
+
+#[test]
+fn patches_and_text_work_on_an_array_root() {
+    let transform = json!({"@jdt.replace": {"@jdt.path": "$[1].enabled", "@jdt.value": true}});
+    let (_, patch) = apply_with_patch(&flags(), &transform).unwrap();
+    assert_eq!(
+        patch,
+        json!([{"op": "replace", "path": "/1/enabled", "value": true}])
+    );
+
+    let text = "[\n  {\"name\": \"search\", \"enabled\": true},\n  {\"name\": \"export\", \"enabled\": false}\n]\n";
+    assert_eq!(
+        apply_text(text, &transform).unwrap(),
+        "[\n  {\"name\": \"search\", \"enabled\": true},\n  {\"name\": \"export\", \"enabled\": true}\n]\n"
+    );
+}
@@ -0,0 +1,155 @@
+use jdt_codegen::{
+    apply, apply_with, resolve_params, ApplyOptions, JdtError, MemoryProvider, Params,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+fn params() -> Params {
+    [
+        ("dbHost", json!("db.prod.internal")),
+        ("dbPort", json!(5432)),
+        ("tls", json!(true)),
+        ("hosts", json!(["a", "b"])),
+    ]
+    .into_iter()
+    .collect()
+}
+
+// ── Whole-value references ──────────────────────────────────────────────
+
+#[test]
+fn param_reference_replaces_value() {
+    let source = json!({"Db": {"Host": "localhost", "Port": 1}});
+    let transform =
+        json!({"Db": {"Host": {"@jdt.param": "dbHost"}, "Port": {"@jdt.param": "dbPort"}}});
+    let result = apply_with(&source, &transform, &params()).unwrap();
+    assert_eq!(
+        result,
+        json!({"Db": {"Host": "db.prod.internal", "Port": 5432}})
+    );
+}
+
+#[test]
+fn param_reference_keeps_structured_values() {
+    let source = json!({"AllowedHosts": []});
+    let transform = json!({"AllowedHosts": {"@jdt.param": "hosts"}});
+    let result = apply_with(&source, &transform, &params()).unwrap();
+    assert_eq!(result, json!({"AllowedHosts": ["a", "b"]}));
+}
+
+#[test]
+fn param_reference_inside_verb_value() {
+    let source = json!({"Db": {"Host": "localhost"}});
+    let transform = json!({
+        "@jdt.replace": {"@jdt.path": "$.Db.Host", "@jdt.value": {"@jdt.param": "dbHost"}}
+    });
+    let result = apply_with(&source, &transform, &params()).unwrap();
+    assert_eq!(result, json!({"Db": {"Host": "db.prod.internal"}}));
+}
+
+#[test]
+fn param_reference_with_matching_type() {
+    let transform = json!({"Port": {"@jdt.param": "dbPort", "@jdt.type": "integer"}});
+    let resolved = resolve_params(&transform, &params()).unwrap();
+    assert_eq!(resolved, json!({"Port": 5432}));
+}
+
+// ── String interpolation ────────────────────────────────────────────────
+
+#[test]
+fn interpolates_scalars_into_strings() {
+    let transform = json!({"Conn": "Server=${dbHost};Port=${dbPort};Tls=${tls}"});
+    let resolved = resolve_params(&transform, &params()).unwrap();
+    assert_eq!(
+        resolved,
+        json!({"Conn": "Server=db.prod.internal;Port=5432;Tls=true"})
+    );
+}
+
+#[test]
+fn interpolation_escape_and_unterminated_reference_are_literal() {
+    let transform = json!({"a": "$${dbHost}", "b": "${dbHost", "c": "$5 and $x"});
+    let resolved = resolve_params(&transform, &Params::new()).unwrap();
+    assert_eq!(
+        resolved,
+        json!({"a": "${dbHost}", "b": "${dbHost", "c": "$5 and $x"})
+    );
+}
+
+#[test]
+fn interpolates_inside_jsonpath() {
+    let source = json!({"Items": [{"env": "prod", "on": false}, {"env": "dev", "on": false}]});
+    let transform = json!({
+        "@jdt.merge": {"@jdt.path": "$.Items[?(@.env == \"${env}\")]", "@jdt.value": {"on": true}}
+    });
+    let mut params = Params::new();
+    params.insert("env", json!("prod"));
+    let result = apply_with(&source, &transform, &params).unwrap();
+    assert_eq!(
+        result,
+        json!({"Items": [{"env": "prod", "on": true}, {"env": "dev", "on": false}]})
+    );
+}
+
+#[test]
+fn plain_apply_leaves_strings_literal() {
+    let transform = json!({"Logs": "${HOME}/logs"});
+    assert_eq!(apply(&json!({}), &transform).unwrap(), transform);
+}
+
+// ── Validation ──────────────────────────────────────────────────────────
+
+#[test]
+fn error_missing_param_reference() {
+    let transform = json!({"Host": {"@jdt.param": "nope"}});
+    let err = resolve_params(&transform, &params()).unwrap_err();
+    assert!(matches!(err, JdtError::MissingParam(name) if name == "nope"));
+}
+
+#[test]
+fn error_param_reference_without_params() {
+    let transform = json!({"Db": {"Host": {"@jdt.param": "dbHost"}}});
+    let err = apply(&json!({}), &transform).unwrap_err();
+    assert!(matches!(err, JdtError::MissingParam(name) if name == "dbHost"));
+    let opts = ApplyOptions {
+        provider: Some(Arc::new(MemoryProvider::new())),
+        ..Default::default()
+    };
+    let err = opts.apply(&json!({}), &transform).unwrap_err();
+    assert!(matches!(err, JdtError::MissingParam(name) if name == "dbHost"));
+}
+
+#[test]
+fn error_missing_interpolated_param() {
+    let transform = json!({"Host": ["x", "${nope}"]});
+    let err = resolve_params(&transform, &params()).unwrap_err();
+    assert!(matches!(err, JdtError::MissingParam(name) if name == "nope"));
+}
+
+#[test]
+fn error_param_type_mismatch() {
+    let transform = json!({"Port": {"@jdt.param": "dbHost", "@jdt.type": "number"}});
+    let err = resolve_params(&transform, &params()).unwrap_err();
+    assert!(matches!(err, JdtError::ParamType { name, .. } if name == "dbHost"));
+}
+
+#[test]
+fn error_interpolating_structured_param() {
+    let transform = json!({"Hosts": "${hosts}"});
+    let err = resolve_params(&transform, &params()).unwrap_err();
+    assert!(matches!(err, JdtError::ParamType { .. }));
+}
+
+#[test]
+fn error_unknown_param_type() {
+    let transform = json!({"Port": {"@jdt.param": "dbPort", "@jdt.type": "float"}});
+    let err = resolve_params(&transform, &params()).unwrap_err();
+    assert!(matches!(err, JdtError::InvalidAttributeValue(_, _)));
+}
+
+#[test]
+fn error_param_name_not_string() {
+    let transform = json!({"Port": {"@jdt.param": 1}});
+    let err = resolve_params(&transform, &params()).unwrap_err();
+    assert!(matches!(err, JdtError::AttributeNotString(_)));
+}
@@ -0,0 +1,281 @@
+use jdt_codegen::{
+    apply, json_patch_to_transform, merge_patch_to_transform, ApplyOptions, JdtError,
+};
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+mod common;
+use common::{json_document, merge_patch};
+
+fn apply_json_patch(source: &Value, patch: &Value) -> Value {
+    let transform = json_patch_to_transform(patch).unwrap();
+    apply(source, &transform).unwrap()
+}
+
+// ── JSON Patch ──────────────────────────────────────────────────────────
+
+#[test]
+fn json_patch_operations_run_in_order() {
+    let source = json!({"a": {"b": 1, "c": [1, 2, 3]}, "d": {"x": 1}});
+    let patch = json!([
+        {"op": "replace", "path": "/a/b", "value": 2},
+        {"op": "remove", "path": "/a/c/1"},
+        {"op": "add", "path": "/a/c/-", "value": 4},
+        {"op": "add", "path": "/d", "value": {"y": 2}},
+        {"op": "add", "path": "/e", "value": [true]},
+        {"op": "move", "from": "/a/b", "path": "/a/renamed"}
+    ]);
+    assert_eq!(
+        apply_json_patch(&source, &patch),
+        json!({"a": {"renamed": 2, "c": [1, 3, 4]}, "d": {"y": 2}, "e": [true]})
+    );
+}
+
+#[test]
+fn json_patch_pointer_escapes() {
+    let source = json!({"a~b": {"c/d": 1}});
+    let patch = json!([{"op": "replace", "path": "/a~0b/c~1d", "value": 2}]);
+    assert_eq!(
+        apply_json_patch(&source, &patch),
+        json!({"a~b": {"c/d": 2}})
+    );
+}
+
+#[test]
+fn json_patch_replace_whole_document() {
+    let patch = json!([{"op": "replace", "path": "", "value": {"new": true}}]);
+    assert_eq!(
+        apply_json_patch(&json!({"old": 1}), &patch),
+        json!({"new": true})
+    );
+}
+
+#[test]
+fn json_patch_becomes_ordered_merge_steps() {
+    let transform = json_patch_to_transform(&json!([{"op": "remove", "path": "/a/0"}])).unwrap();
+    assert_eq!(
+        transform,
+        json!({"@jdt.merge": [{"@jdt.remove": {"@jdt.path": "$['a'][0]", "@jdt.expect": 1}}]})
+    );
+}
+
+#[test]
+fn json_patch_runs_in_strict_mode() {
+    let opts = ApplyOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let patch = json!([
+        {"op": "add", "path": "/new", "value": 1},
+        {"op": "add", "path": "/a", "value": 2},
+        {"op": "add", "path": "/l/-", "value": 3}
+    ]);
+    let transform = json_patch_to_transform(&patch).unwrap();
+    assert_eq!(
+        opts.apply(&json!({"a": 1, "l": []}), &transform).unwrap(),
+        json!({"a": 2, "l": [3], "new": 1})
+    );
+}
+
+#[test]
+fn error_json_patch_missing_path() {
+    let source = json!({"a": {"b": 1}});
+    for patch in [
+        json!([{"op": "remove", "path": "/a/x"}]),
+        json!([{"op": "replace", "path": "/x", "value": 1}]),
+        json!([{"op": "add", "path": "/x/y", "value": 1}]),
+        json!([{"op": "add", "path": "/x/-", "value": 1}]),
+        json!([{"op": "move", "from": "/a/x", "path": "/a/y"}]),
+    ] {
+        assert!(jdt_codegen::apply_json_patch(&source, &patch).is_err());
+        let transform = json_patch_to_transform(&patch).unwrap();
+        let err = apply(&source, &transform).unwrap_err();
+        assert!(
+            matches!(err, JdtError::UnexpectedMatchCount { actual: 0, .. }),
+            "{patch}: {err}"
+        );
+    }
+}
+
+#[test]
+fn error_json_patch_non_object_document() {
+    for op in ["add", "replace"] {
+        let patch = json!([{"op": op, "path": "", "value": [1]}]);
+        let err = json_patch_to_transform(&patch).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "unsupported patch operation: {op} of the whole document with a non-object value"
+            )
+        );
+    }
+}
+
+#[test]
+fn error_json_patch_test_op_unsupported() {
+    let patch = json!([{"op": "test", "path": "/a", "value": 1}]);
+    let err = json_patch_to_transform(&patch).unwrap_err();
+    assert!(matches!(err, JdtError::UnsupportedPatchOp(op) if op == "test"));
+}
+
+#[test]
+fn error_json_patch_array_insert_unsupported() {
+    let patch = json!([{"op": "add", "path": "/a/0", "value": 1}]);
+    let err = json_patch_to_transform(&patch).unwrap_err();
+    assert!(matches!(err, JdtError::UnsupportedPatchOp(_)));
+}
+
+#[test]
+fn json_patch_add_under_non_object_parent_is_unsupported() {
+    // The converted transform cannot check the parent, unlike `apply_json_patch`.
+    let patch = json!([{"op": "add", "path": "/a/x", "value": 1}]);
+    let source = json!({"a": [1, 2]});
+    let err = jdt_codegen::apply_json_patch(&source, &patch).unwrap_err();
+    assert_eq!(err.to_string(), "invalid patch: path not found: /a/x");
+    assert_eq!(apply_json_patch(&source, &patch), json!({"a": {"x": 1}}));
+}
+
+#[test]
+fn json_patch_addresses_any_name() {
+    let names = [
+        "*",
+        "a.b",
+        "a[0]",
+        " spaced ",
+        "it's",
+        "\"q\"",
+        "back\\slash",
+        "",
+        "$",
+    ];
+    let source = Value::Object(
+        names
+            .iter()
+            .map(|n| (n.to_string(), json!({"x": 1})))
+            .collect(),
+    );
+    let mut ops = Vec::new();
+    let mut expected = serde_json::Map::new();
+    for (i, name) in names.iter().enumerate() {
+        let pointer = format!("/{}", name.replace('~', "~0").replace('/', "~1"));
+        match i % 3 {
+            0 => {
+                ops.push(json!({"op": "remove", "path": pointer}));
+            }
+            1 => {
+                ops.push(json!({"op": "replace", "path": format!("{pointer}/x"), "value": i}));
+                expected.insert(name.to_string(), json!({"x": i}));
+            }
+            _ => {
+                let to = format!("{name}!");
+                ops.push(json!({"op": "move", "from": pointer, "path": format!("/{to}")}));
+                expected.insert(to, json!({"x": 1}));
+            }
+        }
+    }
+    assert_eq!(
+        apply_json_patch(&source, &Value::Array(ops)),
+        Value::Object(expected)
+    );
+}
+
+#[test]
+fn error_json_patch_malformed() {
+    for patch in [
+        json!({"op": "remove"}),
+        json!([{"path": "/a"}]),
+        json!([{"op": "replace", "path": "/a"}]),
+        json!([{"op": "remove", "path": "a"}]),
+    ] {
+        let err = json_patch_to_transform(&patch).unwrap_err();
+        assert!(matches!(err, JdtError::InvalidPatch(_)), "{patch}");
+    }
+}
+
+// ── Merge Patch ─────────────────────────────────────────────────────────
+
+#[test]
+fn merge_patch_null_removes_and_arrays_replace() {
+    let transform = merge_patch_to_transform(&json!({"a": null, "b": [1], "c": 2})).unwrap();
+    assert_eq!(
+        transform,
+        json!({
+            "@jdt.remove": ["a"],
+            "b": {"@jdt.arrayMerge": "replace", "@jdt.value": [1]},
+            "c": 2
+        })
+    );
+    let source = json!({"a": 1, "b": [0, 0], "keep": true});
+    assert_eq!(
+        apply(&source, &transform).unwrap(),
+        json!({"b": [1], "c": 2, "keep": true})
+    );
+}
+
+#[test]
+fn merge_patch_rfc_example() {
+    let target = json!({
+        "title": "Goodbye!",
+        "author": {"givenName": "John", "familyName": "Doe"},
+        "tags": ["example", "sample"],
+        "content": "This will be unchanged"
+    });
+    let patch = json!({
+        "title": "Hello!",
+        "phoneNumber": "+01-123-456-7890",
+        "author": {"familyName": null},
+        "tags": ["example"]
+    });
+    let transform = merge_patch_to_transform(&patch).unwrap();
+    assert_eq!(
+        apply(&target, &transform).unwrap(),
+        merge_patch(&target, &patch)
+    );
+}
+
+#[test]
+fn merge_patch_nested_removal_where_source_has_no_object() {
+    let patch = json!({"a": {"b": null, "c": {"d": null, "e": [1]}}});
+    let transform = merge_patch_to_transform(&patch).unwrap();
+    for target in [
+        json!({}),
+        json!({"a": 5}),
+        json!({"a": {"b": 1, "c": {"d": 1, "e": [0]}}}),
+    ] {
+        assert_eq!(
+            apply(&target, &transform).unwrap(),
+            merge_patch(&target, &patch)
+        );
+    }
+}
+
+#[test]
+fn error_merge_patch_jdt_member_names() {
+    for patch in [
+        json!({"a": {"@jdt.remove": "z"}}),
+        json!({"@jdt.value": 1}),
+        json!({"a": [{"@jdt.arrayMerge": "replace", "@jdt.value": []}]}),
+    ] {
+        let err = merge_patch_to_transform(&patch).unwrap_err();
+        assert!(matches!(err, JdtError::InvalidPatch(_)), "{patch}");
+    }
+    let err = merge_patch_to_transform(&json!({"a": {"@jdt.remove": "z"}})).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid patch: member /a/@jdt.remove is reserved for JDT syntax"
+    );
+}
+
+#[test]
+fn error_merge_patch_not_object() {
+    let err = merge_patch_to_transform(&json!([1])).unwrap_err();
+    assert!(matches!(err, JdtError::TransformNotObject));
+}
+
+proptest! {
+    #[test]
+    fn merge_patch_matches_rfc_7396(target in json_document(), patch in json_document()) {
+        let transform = merge_patch_to_transform(&patch).unwrap();
+        prop_assert_eq!(apply(&target, &transform).unwrap(), merge_patch(&target, &patch));
+    }
+}
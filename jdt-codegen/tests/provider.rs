@@ -0,0 +1,139 @@
+use jdt_codegen::{
+    apply, apply_with, apply_with_provider, resolve_refs, ApplyOptions, ArrayMerge, EnvProvider,
+    JdtError, MemoryProvider, Params, ValueProvider,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn secrets() -> MemoryProvider {
+    let mut provider = MemoryProvider::new();
+    provider.insert("env:DB_PASSWORD", json!("hunter2"));
+    provider.insert("vault:db/replicas", json!(["r1", "r2"]));
+    provider
+}
+
+#[test]
+fn ref_is_replaced_by_provider_value() {
+    let source = json!({"Db": {"User": "app", "Password": ""}});
+    let transform = json!({"Db": {"Password": {"@jdt.ref": "env:DB_PASSWORD"}}});
+    let result = apply_with_provider(&source, &transform, &secrets()).unwrap();
+    assert_eq!(
+        result,
+        json!({"Db": {"User": "app", "Password": "hunter2"}})
+    );
+}
+
+#[test]
+fn ref_can_resolve_structured_values() {
+    let transform = json!({"@jdt.replace": {"@jdt.path": "$.Replicas", "@jdt.value": {"@jdt.ref": "vault:db/replicas"}}});
+    let resolved = resolve_refs(&transform, &secrets()).unwrap();
+    assert_eq!(
+        resolved,
+        json!({"@jdt.replace": {"@jdt.path": "$.Replicas", "@jdt.value": ["r1", "r2"]}})
+    );
+}
+
+#[test]
+fn env_provider_reads_environment() {
+    std::env::set_var("JDT_PROVIDER_TEST_VALUE", "from-env");
+    let transform = json!({"Value": {"@jdt.ref": "env:JDT_PROVIDER_TEST_VALUE"}});
+    let resolved = resolve_refs(&transform, &EnvProvider).unwrap();
+    assert_eq!(resolved, json!({"Value": "from-env"}));
+}
+
+#[test]
+fn env_provider_ignores_other_schemes() {
+    assert_eq!(EnvProvider.resolve("vault:PATH"), None);
+}
+
+#[test]
+fn custom_provider() {
+    struct Upper;
+    impl ValueProvider for Upper {
+        fn resolve(&self, reference: &str) -> Option<Value> {
+            Some(Value::String(reference.to_uppercase()))
+        }
+    }
+    let resolved = resolve_refs(&json!({"a": [{"@jdt.ref": "x:y"}]}), &Upper).unwrap();
+    assert_eq!(resolved, json!({"a": ["X:Y"]}));
+}
+
+#[test]
+fn refs_and_params_combine() {
+    let mut params = Params::new();
+    params.insert("user", json!("svc"));
+    let opts = ApplyOptions {
+        params: Some(params),
+        provider: Some(Arc::new(secrets())),
+        array_merge: ArrayMerge::Union,
+        strict: true,
+        ..Default::default()
+    };
+    let transform = json!({
+        "User": "${user}",
+        "Password": {"@jdt.ref": "env:DB_PASSWORD"},
+        "Replicas": {"@jdt.ref": "vault:db/replicas"}
+    });
+    let source = json!({"Replicas": ["r1"]});
+    let (result, trace) = opts.apply_traced(&source, &transform).unwrap();
+    assert_eq!(
+        result,
+        json!({"User": "svc", "Password": "hunter2", "Replicas": ["r1", "r2"]})
+    );
+    assert!(!trace.is_empty());
+
+    let err = opts
+        .apply(&source, &json!({"@jdt.remove": {"@jdt.path": "$.${user}"}}))
+        .unwrap_err();
+    assert!(matches!(err, JdtError::NoMatch(_)));
+}
+
+#[test]
+fn options_resolve_every_layer() {
+    let opts = ApplyOptions {
+        provider: Some(Arc::new(secrets())),
+        ..Default::default()
+    };
+    let layers = [
+        json!({"Password": {"@jdt.ref": "env:DB_PASSWORD"}}),
+        json!({"Missing": {"@jdt.ref": "env:UNKNOWN"}}),
+    ];
+    let err = opts.apply_all(&json!({}), &layers).unwrap_err();
+    assert!(matches!(err, JdtError::Layer { index: 1, .. }));
+}
+
+#[test]
+fn error_unresolved_ref() {
+    let transform = json!({"Password": {"@jdt.ref": "env:JDT_PROVIDER_TEST_UNSET"}});
+    let err = resolve_refs(&transform, &EnvProvider).unwrap_err();
+    assert!(matches!(err, JdtError::UnresolvedRef(r) if r == "env:JDT_PROVIDER_TEST_UNSET"));
+}
+
+#[test]
+fn error_ref_without_provider() {
+    let transform = json!({"Db": {"Password": {"@jdt.ref": "env:DB_PASSWORD"}}});
+    let err = apply(&json!({}), &transform).unwrap_err();
+    assert!(matches!(err, JdtError::UnresolvedRef(r) if r == "env:DB_PASSWORD"));
+    let err = apply_with(&json!({}), &transform, &Params::new()).unwrap_err();
+    assert!(matches!(err, JdtError::UnresolvedRef(r) if r == "env:DB_PASSWORD"));
+    let opts = ApplyOptions {
+        params: Some(Params::new()),
+        ..Default::default()
+    };
+    let err = opts.apply_all(&json!({}), &[transform]).unwrap_err();
+    assert!(matches!(err, JdtError::Layer { index: 0, .. }));
+    // Resolving the refs alone leaves parameter references for a later pass.
+    let transform =
+        json!({"Password": {"@jdt.ref": "env:DB_PASSWORD"}, "User": {"@jdt.param": "user"}});
+    assert_eq!(
+        resolve_refs(&transform, &secrets()).unwrap(),
+        json!({"Password": "hunter2", "User": {"@jdt.param": "user"}})
+    );
+}
+
+#[test]
+fn error_ref_not_string() {
+    let transform = json!({"Password": {"@jdt.ref": 1}});
+    let err = resolve_refs(&transform, &secrets()).unwrap_err();
+    assert!(matches!(err, JdtError::AttributeNotString(_)));
+}
@@ -0,0 +1,122 @@
+use jdt_codegen::{apply, ApplyOptions, JdtError, RenameCollision};
+use serde_json::{json, Value};
+
+fn apply_policy(
+    policy: RenameCollision,
+    source: &Value,
+    transform: &Value,
+) -> Result<Value, JdtError> {
+    let opts = ApplyOptions {
+        rename_collision: policy,
+        ..Default::default()
+    };
+    opts.apply(source, transform)
+}
+
+// ── Per-apply policy ────────────────────────────────────────────────────
+
+#[test]
+fn default_policy_overwrites() {
+    let source = json!({"a": 1, "b": 2});
+    let transform = json!({"@jdt.rename": {"a": "b"}});
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"b": 1}));
+}
+
+#[test]
+fn error_policy_rejects_direct_rename() {
+    let source = json!({"a": 1, "b": 2});
+    let transform = json!({"@jdt.rename": {"a": "b"}});
+    let err = apply_policy(RenameCollision::Error, &source, &transform).unwrap_err();
+    assert!(matches!(err, JdtError::RenameCollision(name) if name == "b"));
+}
+
+#[test]
+fn error_policy_rejects_selector_rename() {
+    let source = json!({"a": 1, "b": 2});
+    let transform = json!({"@jdt.rename": {"@jdt.path": "$.a", "@jdt.value": "b"}});
+    let err = apply_policy(RenameCollision::Error, &source, &transform).unwrap_err();
+    assert!(matches!(err, JdtError::RenameCollision(_)));
+}
+
+#[test]
+fn error_policy_allows_rename_without_collision() {
+    let source = json!({"a": 1});
+    let transform = json!({"@jdt.rename": {"a": "b"}});
+    let result = apply_policy(RenameCollision::Error, &source, &transform).unwrap();
+    assert_eq!(result, json!({"b": 1}));
+}
+
+#[test]
+fn keep_existing_policy_skips_rename() {
+    let source = json!({"a": 1, "b": 2});
+    let transform = json!({"@jdt.rename": {"a": "b"}});
+    let result = apply_policy(RenameCollision::KeepExisting, &source, &transform).unwrap();
+    assert_eq!(result, json!({"a": 1, "b": 2}));
+}
+
+#[test]
+fn merge_policy_deep_merges_into_existing() {
+    let source = json!({
+        "old": {"x": 1, "nested": {"p": 1}, "list": [1]},
+        "new": {"y": 2, "nested": {"q": 2}, "list": [0]}
+    });
+    let transform = json!({"@jdt.rename": {"old": "new"}});
+    let result = apply_policy(RenameCollision::Merge, &source, &transform).unwrap();
+    assert_eq!(
+        result,
+        json!({"new": {"x": 1, "y": 2, "nested": {"p": 1, "q": 2}, "list": [0, 1]}})
+    );
+}
+
+#[test]
+fn merge_policy_scalar_renamed_value_wins() {
+    let source = json!({"a": 1, "b": 2});
+    let transform = json!({"@jdt.rename": {"a": "b"}});
+    let result = apply_policy(RenameCollision::Merge, &source, &transform).unwrap();
+    assert_eq!(result, json!({"b": 1}));
+}
+
+#[test]
+fn rename_to_same_name_is_not_a_collision() {
+    let source = json!({"a": 1});
+    let transform = json!({"@jdt.rename": {"a": "a"}});
+    let result = apply_policy(RenameCollision::Error, &source, &transform).unwrap();
+    assert_eq!(result, json!({"a": 1}));
+}
+
+// ── Per-rename override ─────────────────────────────────────────────────
+
+#[test]
+fn attribute_overrides_apply_policy_in_selector_form() {
+    let source = json!({"a": 1, "b": 2});
+    let transform = json!({
+        "@jdt.rename": {"@jdt.path": "$.a", "@jdt.value": "b", "@jdt.onCollision": "keepExisting"}
+    });
+    let result = apply(&source, &transform).unwrap();
+    assert_eq!(result, json!({"a": 1, "b": 2}));
+}
+
+#[test]
+fn attribute_overrides_apply_policy_in_mapping_form() {
+    let source = json!({"a": 1, "b": 2, "c": 3});
+    let transform = json!({"@jdt.rename": {"a": "b", "c": "d", "@jdt.onCollision": "error"}});
+    let err = apply(&source, &transform).unwrap_err();
+    assert!(matches!(err, JdtError::RenameCollision(_)));
+}
+
+#[test]
+fn attribute_overwrite_beats_error_apply_policy() {
+    let source = json!({"a": 1, "b": 2});
+    let transform = json!({"@jdt.rename": {"a": "b", "@jdt.onCollision": "overwrite"}});
+    let result = apply_policy(RenameCollision::Error, &source, &transform).unwrap();
+    assert_eq!(result, json!({"b": 1}));
+}
+
+#[test]
+fn error_unknown_collision_policy() {
+    let source = json!({"a": 1});
+    let transform = json!({"@jdt.rename": {"a": "b", "@jdt.onCollision": "ignore"}});
+    let err = apply(&source, &transform).unwrap_err();
+    assert!(matches!(err, JdtError::InvalidAttributeValue(_, _)));
+}
@@ -0,0 +1,136 @@
+#![cfg(feature = "toml")]
+
+use jdt_codegen::{apply_toml, parse_toml, to_toml, JdtError};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+#[test]
+fn applies_an_overlay_and_writes_toml() {
+    let source = r#"
+[server]
+host = "localhost"
+port = 8080
+
+[[backends]]
+name = "a"
+weight = 1.5
+"#;
+    let transform = json!({
+        "server": {"host": "0.0.0.0", "tls": true},
+        "backends": [{"name": "b", "weight": 2.0}]
+    });
+    let out = apply_toml(source, &transform).unwrap();
+    assert_eq!(
+        parse_toml(&out).unwrap(),
+        json!({
+            "server": {"host": "0.0.0.0", "port": 8080, "tls": true},
+            "backends": [{"name": "a", "weight": 1.5}, {"name": "b", "weight": 2.0}]
+        })
+    );
+    assert!(out.contains("[[backends]]"), "{out}");
+}
+
+#[test]
+fn datetimes_round_trip() {
+    let source = "created = 1979-05-27T07:32:00Z\nday = 1979-05-27\nnote = \"x\"\n";
+    assert_eq!(
+        parse_toml(source).unwrap(),
+        json!({"created": "1979-05-27T07:32:00Z", "day": "1979-05-27", "note": "x"})
+    );
+    let out = apply_toml(
+        source,
+        &json!({"day": "2024-01-02", "note": "2024-01-02", "extra": "2024-01-02"}),
+    )
+    .unwrap();
+    let mut lines: Vec<_> = out.lines().collect();
+    lines.sort_unstable();
+    assert_eq!(
+        lines,
+        [
+            "created = 1979-05-27T07:32:00Z",
+            "day = 2024-01-02",
+            "extra = \"2024-01-02\"",
+            "note = \"2024-01-02\""
+        ]
+    );
+    // A replaced date-time that no longer parses as one is written as a string.
+    let out = apply_toml(source, &json!({"day": "soon"})).unwrap();
+    assert!(out.contains("day = \"soon\""), "{out}");
+}
+
+#[test]
+fn strings_added_to_datetime_arrays_stay_datetimes() {
+    let source = "d = [1979-05-27]\n";
+    let out = apply_toml(source, &json!({"d": ["1980-01-01"]})).unwrap();
+    assert_eq!(
+        parse_toml(&out).unwrap(),
+        json!({"d": ["1979-05-27", "1980-01-01"]})
+    );
+    assert!(!out.contains('"'), "{out}");
+    let err = apply_toml(source, &json!({"d": ["later"]})).unwrap_err();
+    assert!(
+        err.to_string().contains("cannot mix datetime and string"),
+        "{err}"
+    );
+}
+
+#[test]
+fn nulls_are_not_representable() {
+    let err = apply_toml("[db]\nhost = \"x\"\n", &json!({"db": {"host": null}})).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "value at \"/db/host\" cannot be written as TOML: TOML has no null"
+    );
+    let err = to_toml(&json!({"list": [1, null]})).unwrap_err();
+    assert!(matches!(err, JdtError::TomlValue { ref path, .. } if path == "/list/1"));
+}
+
+#[test]
+fn root_must_be_a_table() {
+    for value in [json!([1, "a"]), json!("text"), json!(null)] {
+        let err = to_toml(&value).unwrap_err();
+        assert!(
+            matches!(err, JdtError::TomlValue { ref path, .. } if path.is_empty()),
+            "{value}"
+        );
+    }
+    let err = to_toml(&json!([1, "a"])).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "value at \"\" cannot be written as TOML: a TOML document must be a table"
+    );
+}
+
+#[test]
+fn integers_above_i64_are_rejected() {
+    let err = to_toml(&json!({"big": u64::MAX})).unwrap_err();
+    assert!(matches!(err, JdtError::TomlValue { ref path, .. } if path == "/big"));
+}
+
+#[test]
+fn heterogeneous_top_level_arrays_are_rejected() {
+    let err = to_toml(&json!({"mixed": [1, 2, "three"]})).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "value at \"/mixed/2\" cannot be written as TOML: a top-level array cannot mix integer and string"
+    );
+    let err = apply_toml("ports = [80, 443]\n", &json!({"ports": [{"tls": true}]})).unwrap_err();
+    assert!(matches!(err, JdtError::TomlValue { ref path, .. } if path == "/ports/2"));
+    // Arrays of one type, and mixed arrays inside tables, are written.
+    let value = json!({"ports": [80, 443], "t": {"mixed": [1, "two", {"three": 3}]}});
+    assert_eq!(parse_toml(&to_toml(&value).unwrap()).unwrap(), value);
+}
+
+#[test]
+fn parse_errors_give_the_location() {
+    let err = parse_toml("a = 1\nb = \n").unwrap_err();
+    match err {
+        JdtError::Toml(message) => assert!(message.starts_with("line 2, column "), "{message}"),
+        other => panic!("unexpected error {other:?}"),
+    }
+    let err = parse_toml("x = [1.0, nan]\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid TOML: at \"/x/1\": NaN has no JSON equivalent"
+    );
+}
@@ -0,0 +1,159 @@
+use jdt_codegen::{apply, apply_traced, ApplyOptions, ArrayMerge, TraceEvent};
+use serde_json::{json, Value};
+
+fn traced(source: &Value, transform: &Value) -> Vec<TraceEvent> {
+    let (result, trace) = apply_traced(source, transform).unwrap();
+    assert_eq!(result, apply(source, transform).unwrap());
+    trace
+}
+
+/// `(verb, location, matched)` of each event.
+fn outline(trace: &[TraceEvent]) -> Vec<(&str, &str, Vec<&str>)> {
+    trace
+        .iter()
+        .map(|e| {
+            let matched = e.matched.iter().map(String::as_str).collect();
+            (e.verb, e.location.as_str(), matched)
+        })
+        .collect()
+}
+
+#[test]
+fn remove_by_name() {
+    let trace = traced(&json!({"a": 1, "b": 2}), &json!({"@jdt.remove": "a"}));
+    assert_eq!(
+        trace,
+        vec![TraceEvent {
+            verb: "remove",
+            location: "/@jdt.remove".to_string(),
+            selector: None,
+            matched: vec!["/a".to_string()],
+            old: vec![Some("1".to_string())],
+            new: None,
+            changed: true,
+        }]
+    );
+}
+
+#[test]
+fn selector_replace_in_nested_node() {
+    let source = json!({"Db": {"Host": "localhost", "Port": 1}});
+    let transform = json!({
+        "Db": {"@jdt.replace": {"@jdt.path": "$.Host", "@jdt.value": "prod"}}
+    });
+    let trace = traced(&source, &transform);
+    assert_eq!(trace.len(), 1);
+    let event = &trace[0];
+    assert_eq!(event.verb, "replace");
+    assert_eq!(event.location, "/Db/@jdt.replace");
+    assert_eq!(event.selector.as_deref(), Some("$.Host"));
+    assert_eq!(event.matched, ["/Db/Host"]);
+    assert_eq!(event.old, [Some("\"localhost\"".to_string())]);
+    assert_eq!(event.new, Some(json!("prod")));
+}
+
+#[test]
+fn events_follow_execution_order() {
+    let source = json!({"a": {"x": 1}, "b": [1], "c": 1, "d": 2});
+    let transform = json!({
+        "a": {"y": 2},
+        "b": [2],
+        "@jdt.rename": [{"c": "cc"}, {"@jdt.path": "$.d", "@jdt.value": "dd"}],
+        "@jdt.remove": {"@jdt.path": "$.missing"}
+    });
+    assert_eq!(
+        outline(&traced(&source, &transform)),
+        vec![
+            ("default", "/a/y", vec!["/a/y"]),
+            ("remove", "/@jdt.remove", vec![]),
+            ("rename", "/@jdt.rename/0", vec!["/c"]),
+            ("rename", "/@jdt.rename/1", vec!["/d"]),
+            ("default", "/b", vec!["/b"]),
+        ]
+    );
+}
+
+#[test]
+fn merge_reports_nested_operations() {
+    let source = json!({"Items": [{"env": "prod"}, {"env": "dev"}]});
+    let transform = json!({
+        "@jdt.merge": {
+            "@jdt.path": "$.Items[?(@.env == \"prod\")]",
+            "@jdt.value": {"on": true, "@jdt.remove": "env"}
+        }
+    });
+    let trace = traced(&source, &transform);
+    assert_eq!(
+        outline(&trace),
+        vec![
+            ("merge", "/@jdt.merge", vec!["/Items/0"]),
+            (
+                "remove",
+                "/@jdt.merge/@jdt.value/@jdt.remove",
+                vec!["/Items/0/env"]
+            ),
+            ("default", "/@jdt.merge/@jdt.value/on", vec!["/Items/0/on"]),
+        ]
+    );
+    assert_eq!(trace[0].old, [Some("{1 key}".to_string())]);
+    assert_eq!(trace[2].old, [None]);
+}
+
+#[test]
+fn keyed_array_elements_locate_their_transform_element() {
+    let opts = ApplyOptions {
+        array_merge: ArrayMerge::Keyed {
+            key: "id".to_string(),
+            remove_unmatched: false,
+        },
+        ..Default::default()
+    };
+    let source = json!({"list": [{"id": 1}, {"id": 2}]});
+    let transform = json!({"list": [{"id": 2, "v": 1}]});
+    let (_, trace) = opts.apply_traced(&source, &transform).unwrap();
+    assert_eq!(
+        outline(&trace),
+        vec![
+            ("default", "/list", vec!["/list"]),
+            ("default", "/list/0/id", vec!["/list/1/id"]),
+            ("default", "/list/0/v", vec!["/list/1/v"]),
+        ]
+    );
+}
+
+#[test]
+fn old_values_are_summarized() {
+    let long = "x".repeat(50);
+    let source = json!({"o": {"a": 1, "b": 2}, "l": [1, 2, 3], "s": long, "n": null});
+    let transform = json!({"@jdt.remove": ["o", "l", "s", "n"]});
+    let old: Vec<_> = traced(&source, &transform)
+        .into_iter()
+        .flat_map(|e| e.old)
+        .collect();
+    assert_eq!(
+        old,
+        [
+            Some("{2 keys}".to_string()),
+            Some("[3 items]".to_string()),
+            Some(format!("\"{}…\"", "x".repeat(40))),
+            Some("null".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn events_render_as_json() {
+    let trace = traced(&json!({"a": 1}), &json!({"a": 2}));
+    assert_eq!(
+        trace[0].to_json(),
+        json!({
+            "verb": "default",
+            "location": "/a",
+            "selector": null,
+            "matched": ["/a"],
+            "old": ["1"],
+            "new": 2,
+            "changed": true
+        })
+    );
+}
@@ -37,6 +37,8 @@ fn error_transform_not_object_null() {
 
 // ── SourceNotObject ──────────────────────────────────────────────────────
 
+// Default keys need an object; verbs alone also apply to other roots (see non_object_roots.rs).
+
 #[test]
 fn error_source_not_object_string() {
     let source = json!("not an object");
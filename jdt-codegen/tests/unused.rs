@@ -0,0 +1,117 @@
+use jdt_codegen::{apply_with_unused, ApplyOptions, JdtError};
+use serde_json::json;
+
+/// `(verb, location)` of each operation that changed nothing.
+fn unused(source: &serde_json::Value, transform: &serde_json::Value) -> Vec<(String, String)> {
+    let (_, unused) = apply_with_unused(source, transform).unwrap();
+    unused
+        .into_iter()
+        .map(|e| (e.verb.to_string(), e.location))
+        .collect()
+}
+
+fn entry(verb: &str, location: &str) -> (String, String) {
+    (verb.to_string(), location.to_string())
+}
+
+#[test]
+fn effective_transform_has_no_unused_entries() {
+    let source = json!({"a": 1, "b": {"c": 2}, "list": [1]});
+    let transform = json!({
+        "a": 2,
+        "b": {"@jdt.rename": {"c": "d"}},
+        "list": [2],
+        "@jdt.remove": {"@jdt.path": "$.b"}
+    });
+    assert_eq!(unused(&source, &transform), vec![]);
+}
+
+#[test]
+fn reports_zero_match_selectors_and_missing_keys() {
+    let source = json!({"a": 1, "Items": [{"env": "dev"}]});
+    let transform = json!({
+        "@jdt.remove": ["gone", {"@jdt.path": "$.Items[?(@.env == \"prod\")]"}],
+        "@jdt.replace": {"@jdt.path": "$.missing", "@jdt.value": 1},
+        "@jdt.rename": {"old": "new"},
+        "@jdt.merge": {"@jdt.path": "$.nothing", "@jdt.value": {"x": 1}}
+    });
+    let (result, events) = apply_with_unused(&source, &transform).unwrap();
+    assert_eq!(result, source);
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.location.as_str(), e.selector.as_deref()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("/@jdt.remove/0", None),
+            ("/@jdt.remove/1", Some("$.Items[?(@.env == \"prod\")]")),
+            ("/@jdt.replace", Some("$.missing")),
+            ("/@jdt.rename", None),
+            ("/@jdt.merge", Some("$.nothing")),
+        ]
+    );
+    assert!(events[1].matched.is_empty());
+}
+
+#[test]
+fn reports_default_keys_already_set() {
+    let source = json!({"a": 1, "b": {"c": [1, 2]}, "d": [1]});
+    let transform = json!({
+        "a": 1,
+        "b": {"c": {"@jdt.arrayMerge": "union", "@jdt.value": [2]}},
+        "d": {"@jdt.arrayMerge": "replace", "@jdt.value": [1]},
+        "e": null
+    });
+    assert_eq!(
+        unused(&source, &transform),
+        vec![
+            entry("default", "/b/c"),
+            entry("default", "/a"),
+            entry("default", "/d")
+        ]
+    );
+}
+
+#[test]
+fn merge_is_unused_when_every_nested_operation_is() {
+    let source = json!({"Items": [{"on": true}, {"on": false}]});
+    let noop = json!({"@jdt.merge": {"@jdt.path": "$.Items[0]", "@jdt.value": {"on": true}}});
+    assert_eq!(
+        unused(&source, &noop),
+        vec![
+            entry("merge", "/@jdt.merge"),
+            entry("default", "/@jdt.merge/@jdt.value/on"),
+        ]
+    );
+    let partly = json!({"@jdt.merge": [
+        {"@jdt.path": "$.Items[0,1]", "@jdt.value": {"on": true}}
+    ]});
+    assert_eq!(
+        unused(&source, &partly),
+        vec![entry("default", "/@jdt.merge/0/@jdt.value/on")]
+    );
+}
+
+#[test]
+fn strict_mode_rejects_zero_match_selectors() {
+    let strict = ApplyOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let source = json!({"a": {"b": 1}});
+    for transform in [
+        json!({"@jdt.remove": {"@jdt.path": "$.missing"}}),
+        json!({"@jdt.replace": {"@jdt.path": "$.missing", "@jdt.value": 1}}),
+        json!({"@jdt.rename": {"@jdt.path": "$.missing", "@jdt.value": "x"}}),
+        json!({"a": {"@jdt.merge": {"@jdt.path": "$.missing", "@jdt.value": {}}}}),
+    ] {
+        let err = strict.apply(&source, &transform).unwrap_err();
+        assert!(
+            matches!(&err, JdtError::NoMatch(s) if s == "$.missing"),
+            "{err}"
+        );
+        assert!(ApplyOptions::default().apply(&source, &transform).is_ok());
+    }
+    let matching = json!({"@jdt.remove": {"@jdt.path": "$.a.b"}});
+    assert_eq!(strict.apply(&source, &matching).unwrap(), json!({"a": {}}));
+}
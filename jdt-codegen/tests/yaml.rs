@@ -0,0 +1,156 @@
+#![cfg(feature = "yaml")]
+
+use jdt_codegen::{apply_yaml, parse_yaml, to_yaml, ApplyOptions, ArrayMerge, JdtError};
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+
+fn yaml_error(text: &str) -> String {
+    match parse_yaml(text) {
+        Err(JdtError::Yaml(message)) => message,
+        other => panic!("expected a YAML error for {text:?}, got {other:?}"),
+    }
+}
+
+#[test]
+fn applies_to_every_document_of_a_stream() {
+    let source = "\
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+---
+apiVersion: v1
+kind: Service
+metadata:
+  name: web
+";
+    let transform = json!({"metadata": {"namespace": "prod"}});
+    let out = apply_yaml(source, &transform).unwrap();
+    assert_eq!(
+        parse_yaml(&out).unwrap(),
+        vec![
+            json!({"apiVersion": "apps/v1", "kind": "Deployment", "metadata": {"name": "web", "namespace": "prod"}}),
+            json!({"apiVersion": "v1", "kind": "Service", "metadata": {"name": "web", "namespace": "prod"}}),
+        ]
+    );
+    assert_eq!(out.matches("---").count(), 1);
+}
+
+#[test]
+fn empty_documents_are_kept() {
+    let out = apply_yaml("a: 1\n---\n---\nb: 2\n", &json!({"c": 3})).unwrap();
+    assert_eq!(
+        parse_yaml(&out).unwrap(),
+        vec![
+            json!({"a": 1, "c": 3}),
+            Value::Null,
+            json!({"b": 2, "c": 3})
+        ]
+    );
+}
+
+#[test]
+fn transforms_can_be_written_in_yaml() {
+    let transform = parse_yaml(
+        "\
+replicas: 3
+'@jdt.remove': debug
+'@jdt.rename':
+  '@jdt.path': $.image
+  '@jdt.value': containerImage
+",
+    )
+    .unwrap()
+    .remove(0);
+    let opts = ApplyOptions {
+        array_merge: ArrayMerge::Replace,
+        ..Default::default()
+    };
+    let out = opts
+        .apply_yaml("replicas: 1\ndebug: true\nimage: web:1\n", &transform)
+        .unwrap();
+    assert_eq!(
+        parse_yaml(&out).unwrap(),
+        vec![json!({"replicas": 3, "containerImage": "web:1"})]
+    );
+}
+
+#[test]
+fn aliases_are_expanded_and_merge_keys_applied() {
+    let text = "\
+defaults: &defaults
+  cpu: 100m
+  memory: 64Mi
+web:
+  <<: *defaults
+  memory: 128Mi
+worker: *defaults
+";
+    assert_eq!(
+        parse_yaml(text).unwrap(),
+        vec![json!({
+            "defaults": {"cpu": "100m", "memory": "64Mi"},
+            "web": {"cpu": "100m", "memory": "128Mi"},
+            "worker": {"cpu": "100m", "memory": "64Mi"}
+        })]
+    );
+}
+
+#[test]
+fn scalar_keys_become_strings() {
+    assert_eq!(
+        parse_yaml("8080: http\ntrue: yes\n1.5: x\n").unwrap(),
+        vec![json!({"8080": "http", "true": "yes", "1.5": "x"})]
+    );
+    assert_eq!(
+        yaml_error("1: a\n'1': b\n"),
+        "document 0, at \"\": duplicate key \"1\""
+    );
+}
+
+#[test]
+fn yaml_only_constructs_are_rejected() {
+    assert_eq!(
+        yaml_error("a: 1\n---\nResources:\n  Bucket:\n    Name: !Ref BucketName\n"),
+        "document 1, at \"/Resources/Bucket/Name\": tag !Ref is not supported"
+    );
+    assert_eq!(
+        yaml_error("list:\n  - ? [1, 2]\n    : pair\n"),
+        "document 0, at \"/list/0\": only scalar keys are supported"
+    );
+    assert_eq!(
+        yaml_error("~: x\n"),
+        "document 0, at \"\": null keys are not supported"
+    );
+    assert_eq!(
+        yaml_error("a: [1, .nan]\n"),
+        "document 0, at \"/a/1\": .nan has no JSON equivalent"
+    );
+}
+
+#[test]
+fn syntax_errors_give_the_location() {
+    let message = yaml_error("a: 1\nb: [1, 2\n");
+    assert!(message.starts_with("document 0: "), "{message}");
+    assert!(message.contains("line"), "{message}");
+}
+
+#[test]
+fn transform_errors_name_the_document() {
+    let err = apply_yaml("a: 1\n---\n- 1\n", &json!({"b": 2})).unwrap_err();
+    assert!(matches!(
+        &err,
+        JdtError::Document { index: 1, source } if matches!(**source, JdtError::SourceNotObject)
+    ));
+    assert_eq!(err.to_string(), "document 1: source must be a JSON object");
+}
+
+#[test]
+fn output_round_trips() {
+    let documents = vec![
+        json!({"name": "web", "ports": [80, 443], "labels": {"tier": "1"}, "on": "yes"}),
+        json!({"empty": {}, "none": null, "text": "a: b\nc"}),
+    ];
+    let text = to_yaml(&documents).unwrap();
+    assert_eq!(parse_yaml(&text).unwrap(), documents);
+}
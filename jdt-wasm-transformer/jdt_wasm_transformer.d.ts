@@ -9,11 +9,35 @@
  */
 export function version(): string;
 
+/**
+ * Optional settings accepted by `transform()` and `transform_pretty()`,
+ * passed as a JSON string.
+ */
+export interface TransformOptions {
+  /**
+   * Values for the transform's `{"@jdt.param": "name"}` and `${name}` references.
+   * Without it, references are not resolved and `${...}` strings stay literal.
+   */
+  params?: Record<string, unknown>;
+  /**
+   * Syntax of the source and transform: `"jsonc"` accepts `//` and `/* *\/` comments
+   * and trailing commas, and `"json5"` also accepts the rest of JSON5 except
+   * `Infinity` and `NaN`.
+   */
+  format?: "json" | "jsonc" | "json5";
+  /**
+   * Match keys ignoring case, as .NET configuration does. Matched keys keep the
+   * source spelling; new keys and rename targets use the transform's.
+   */
+  caseInsensitive?: boolean;
+}
+
 /**
  * Apply a JDT transform to a source JSON document.
  * 
  * @param source_json - The source JSON document as a string
  * @param transform_json - The JDT transform specification as a string
+ * @param options_json - Optional `TransformOptions` as a JSON string
  * @returns The transformed JSON as a string
  * @throws Error if transformation fails
  * 
@@ -26,7 +50,7 @@ export function version(): string;
  * // { name: "example", version: "2.0.0" }
  * ```
  */
-export function transform(source_json: string, transform_json: string): string;
+export function transform(source_json: string, transform_json: string, options_json?: string): string;
 
 /**
  * Apply a JDT transform with pretty-printed output.
@@ -35,10 +59,84 @@ export function transform(source_json: string, transform_json: string): string;
  * 
  * @param source_json - The source JSON document as a string
  * @param transform_json - The JDT transform specification as a string
+ * @param options_json - Optional `TransformOptions` as a JSON string
  * @returns The transformed JSON as a pretty-printed string
  * @throws Error if transformation fails
  */
-export function transform_pretty(source_json: string, transform_json: string): string;
+export function transform_pretty(source_json: string, transform_json: string, options_json?: string): string;
+
+/**
+ * Apply an ordered list of JDT transforms to a source JSON document.
+ * 
+ * @param source_json - The source JSON document as a string
+ * @param transforms_json - A JSON array of transform specifications, applied in order
+ * @param options_json - Optional `TransformOptions` as a JSON string; `params` apply to every layer
+ * @returns The transformed JSON as a string
+ * @throws Error naming the zero-based index of the failing transform
+ * 
+ * @example
+ * ```typescript
+ * const result = transform_all(
+ *   JSON.stringify({ port: 80 }),
+ *   JSON.stringify([{ port: 8080 }, { host: "eu-west.example.com" }])
+ * );
+ * // { host: "eu-west.example.com", port: 8080 }
+ * ```
+ */
+export function transform_all(source_json: string, transforms_json: string, options_json?: string): string;
+
+/**
+ * One operation performed by a transform, as reported by `transform_traced()`.
+ */
+export interface TraceEvent {
+  /** `remove`, `replace`, `rename`, `merge`, or `default` for the default transformation. */
+  verb: string;
+  /** JSON Pointer to the verb's value (or the default key) in the transform. */
+  location: string;
+  /** The `@jdt.path` selector of an attributed call. */
+  selector: string | null;
+  /** JSON Pointers of the source nodes the operation targets. */
+  matched: string[];
+  /** Summary of the value at each matched path before the operation; `null` when absent. */
+  old: (string | null)[];
+  /** The value written: the replacement, merged value or new name. */
+  new: unknown;
+  /** Whether the operation, including any nested ones, changed the document. */
+  changed: boolean;
+}
+
+/**
+ * Apply a JDT transform and report every operation it performed.
+ * 
+ * @param source_json - The source JSON document as a string
+ * @param transform_json - The JDT transform specification as a string
+ * @param options_json - Optional `TransformOptions` as a JSON string
+ * @returns A JSON string of `{ result: unknown, trace: TraceEvent[] }`
+ * @throws Error if transformation fails
+ * 
+ * @example
+ * ```typescript
+ * const { trace } = JSON.parse(transform_traced(
+ *   JSON.stringify({ a: 1, b: 2 }),
+ *   JSON.stringify({ "@jdt.remove": "a" })
+ * ));
+ * // [{ verb: "remove", location: "/@jdt.remove", selector: null,
+ * //    matched: ["/a"], old: ["1"], new: null, changed: true }]
+ * ```
+ */
+export function transform_traced(source_json: string, transform_json: string, options_json?: string): string;
+
+/**
+ * Apply a JDT transform to JSON text, rewriting only what it changes.
+ * 
+ * @param source_json - The source JSON document as a string
+ * @param transform_json - The JDT transform specification as a string
+ * @param options_json - Optional `TransformOptions` as a JSON string
+ * @returns The source text with the changed values, keys and members rewritten and
+ *   all other formatting, including JSONC comments, kept
+ * @throws Error if the source is invalid or transformation fails
+ */
+export function transform_text(source_json: string, transform_json: string, options_json?: string): string;
 
 /**
  * Validate a JDT transform specification without applying it.
@@ -1,3 +1,4 @@
+use jdt_codegen::InputFormat;
 use serde_json::Value;
 use wasm_bindgen::prelude::*;
 
@@ -12,6 +13,14 @@ pub fn version() -> String {
 /// # Arguments
 /// * `source_json` - The source JSON document as a string
 /// * `transform_json` - The JDT transform specification as a string
+/// * `options_json` - Optional JSON object with extra settings:
+///   - `params`: object binding the transform's `@jdt.param` / `${name}` references;
+///     without it strings are used as written and a `@jdt.param` object fails
+///   - `format`: `"json"` (default), `"jsonc"` to accept comments and trailing commas, or
+///     `"json5"`, for the source and transform
+///   - `caseInsensitive`: match keys ignoring case, as .NET configuration does
+///
+/// There is no value provider, so a `@jdt.ref` reference always fails as unresolved.
 ///
 /// # Returns
 /// The transformed JSON as a string, or an error if transformation fails
@@ -20,25 +29,18 @@ pub fn version() -> String {
 /// Returns a JsValue error if:
 /// - Source JSON is invalid
 /// - Transform JSON is invalid
+/// - Options JSON is invalid
 /// - Transform contains invalid JDT syntax
+/// - A referenced parameter is missing or has the wrong type
+/// - Transform contains a `@jdt.ref` reference
 /// - Transform execution fails
 #[wasm_bindgen]
-pub fn transform(source_json: &str, transform_json: &str) -> Result<String, JsValue> {
-    // Strip BOM if present
-    let source_json = jdt_codegen::strip_bom(source_json);
-    let transform_json = jdt_codegen::strip_bom(transform_json);
-
-    // Parse source JSON
-    let source: Value = serde_json::from_str(source_json)
-        .map_err(|e| JsValue::from_str(&format!("Invalid source JSON: {}", e)))?;
-
-    // Parse transform JSON
-    let transform: Value = serde_json::from_str(transform_json)
-        .map_err(|e| JsValue::from_str(&format!("Invalid transform JSON: {}", e)))?;
-
-    // Apply transform
-    let result = jdt_codegen::apply(&source, &transform)
-        .map_err(|e| JsValue::from_str(&format!("Transform failed: {}", e)))?;
+pub fn transform(
+    source_json: &str,
+    transform_json: &str,
+    options_json: Option<String>,
+) -> Result<String, JsValue> {
+    let result = apply_json(source_json, transform_json, options_json.as_deref())?;
 
     // Serialize result
     serde_json::to_string(&result)
@@ -49,23 +51,203 @@ pub fn transform(source_json: &str, transform_json: &str) -> Result<String, JsVa
 ///
 /// Same as `transform()` but returns formatted JSON with indentation.
 #[wasm_bindgen]
-pub fn transform_pretty(source_json: &str, transform_json: &str) -> Result<String, JsValue> {
-    let source_json = jdt_codegen::strip_bom(source_json);
-    let transform_json = jdt_codegen::strip_bom(transform_json);
+pub fn transform_pretty(
+    source_json: &str,
+    transform_json: &str,
+    options_json: Option<String>,
+) -> Result<String, JsValue> {
+    let result = apply_json(source_json, transform_json, options_json.as_deref())?;
+
+    serde_json::to_string_pretty(&result)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
 
-    let source: Value = serde_json::from_str(source_json)
-        .map_err(|e| JsValue::from_str(&format!("Invalid source JSON: {}", e)))?;
+/// Apply an ordered list of JDT transforms to a source JSON document.
+///
+/// # Arguments
+/// * `source_json` - The source JSON document as a string
+/// * `transforms_json` - A JSON array of transform specifications, applied in order
+/// * `options_json` - Optional settings, as for `transform()`; `params` apply to every layer
+///
+/// # Returns
+/// The transformed JSON as a string. A failing transform is reported with its
+/// zero-based position in the array.
+#[wasm_bindgen]
+pub fn transform_all(
+    source_json: &str,
+    transforms_json: &str,
+    options_json: Option<String>,
+) -> Result<String, JsValue> {
+    let options = parse_options(options_json.as_deref())?;
+    let source = parse_json(source_json, options.format, "source")?;
 
-    let transform: Value = serde_json::from_str(transform_json)
-        .map_err(|e| JsValue::from_str(&format!("Invalid transform JSON: {}", e)))?;
+    let transforms = parse_json(transforms_json, options.format, "transforms")?;
+    let Value::Array(transforms) = transforms else {
+        return Err(JsValue::from_str(
+            "Invalid transforms JSON: expected an array",
+        ));
+    };
 
-    let result = jdt_codegen::apply(&source, &transform)
+    let result = options
+        .apply_options()
+        .apply_all(&source, &transforms)
         .map_err(|e| JsValue::from_str(&format!("Transform failed: {}", e)))?;
 
-    serde_json::to_string_pretty(&result)
+    serde_json::to_string(&result)
         .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
 }
 
+/// Apply a JDT transform and report every operation it performed.
+///
+/// Takes the same arguments as `transform()`.
+///
+/// # Returns
+/// A JSON object `{"result": ..., "trace": [...]}`. Each trace event has the
+/// `verb`, its `location` in the transform and `selector`, the `matched` source
+/// paths with a summary of each `old` value, the `new` value written, and whether
+/// it `changed` the document.
+#[wasm_bindgen]
+pub fn transform_traced(
+    source_json: &str,
+    transform_json: &str,
+    options_json: Option<String>,
+) -> Result<String, JsValue> {
+    let (source, transform, options) =
+        parse_inputs(source_json, transform_json, options_json.as_deref())?;
+
+    let (result, trace) = options
+        .apply_options()
+        .apply_traced(&source, &transform)
+        .map_err(|e| JsValue::from_str(&format!("Transform failed: {}", e)))?;
+    let trace: Vec<Value> = trace.iter().map(jdt_codegen::TraceEvent::to_json).collect();
+
+    serde_json::to_string(&serde_json::json!({ "result": result, "trace": trace }))
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
+/// Apply a JDT transform to JSON text, keeping its formatting.
+///
+/// Takes the same arguments as `transform()`. With `format: "jsonc"` or `"json5"`,
+/// comments and other source syntax are kept too.
+///
+/// # Returns
+/// The source text with only the values, keys and members the transform changes
+/// rewritten; indentation, blank lines, number spellings and every other byte are kept.
+#[wasm_bindgen]
+pub fn transform_text(
+    source_json: &str,
+    transform_json: &str,
+    options_json: Option<String>,
+) -> Result<String, JsValue> {
+    let options = parse_options(options_json.as_deref())?;
+    let transform = parse_json(transform_json, options.format, "transform")?;
+    options
+        .apply_options()
+        .apply_text(source_json, &transform)
+        .map_err(|e| match e {
+            jdt_codegen::JdtError::Syntax { .. } => {
+                JsValue::from_str(&format!("Invalid source JSON: {}", e))
+            }
+            e => JsValue::from_str(&format!("Transform failed: {}", e)),
+        })
+}
+
+fn apply_json(
+    source_json: &str,
+    transform_json: &str,
+    options_json: Option<&str>,
+) -> Result<Value, JsValue> {
+    let (source, transform, options) = parse_inputs(source_json, transform_json, options_json)?;
+
+    // Apply transform
+    options
+        .apply_options()
+        .apply(&source, &transform)
+        .map_err(|e| JsValue::from_str(&format!("Transform failed: {}", e)))
+}
+
+fn parse_inputs(
+    source_json: &str,
+    transform_json: &str,
+    options_json: Option<&str>,
+) -> Result<(Value, Value, Options), JsValue> {
+    let options = parse_options(options_json)?;
+    let source = parse_json(source_json, options.format, "source")?;
+    let transform = parse_json(transform_json, options.format, "transform")?;
+    Ok((source, transform, options))
+}
+
+/// Parse `text` in `format`, after stripping a BOM; `what` names it in errors.
+fn parse_json(text: &str, format: InputFormat, what: &str) -> Result<Value, JsValue> {
+    let text = jdt_codegen::strip_bom(text);
+    match format {
+        InputFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+        InputFormat::Jsonc => jdt_codegen::parse_jsonc(text).map_err(|e| e.to_string()),
+        InputFormat::Json5 => jdt_codegen::parse_json5(text).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| JsValue::from_str(&format!("Invalid {} JSON: {}", what, e)))
+}
+
+#[derive(Default)]
+struct Options {
+    params: Option<jdt_codegen::Params>,
+    format: InputFormat,
+    case_insensitive: bool,
+}
+
+impl Options {
+    fn apply_options(&self) -> jdt_codegen::ApplyOptions {
+        jdt_codegen::ApplyOptions {
+            input_format: self.format,
+            case_insensitive: self.case_insensitive,
+            params: self.params.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+fn parse_options(options_json: Option<&str>) -> Result<Options, JsValue> {
+    let mut options = Options::default();
+    let Some(options_json) = options_json else {
+        return Ok(options);
+    };
+    let value: Value = serde_json::from_str(jdt_codegen::strip_bom(options_json))
+        .map_err(|e| JsValue::from_str(&format!("Invalid options JSON: {}", e)))?;
+    let Value::Object(mut obj) = value else {
+        return Err(JsValue::from_str("Invalid options: expected a JSON object"));
+    };
+    match obj.remove("params") {
+        Some(Value::Object(params)) => options.params = Some(params.into()),
+        Some(_) => {
+            return Err(JsValue::from_str(
+                "Invalid options: params must be an object",
+            ))
+        }
+        None => {}
+    }
+    match obj.remove("format") {
+        Some(Value::String(format)) if format == "json" => options.format = InputFormat::Json,
+        Some(Value::String(format)) if format == "jsonc" => options.format = InputFormat::Jsonc,
+        Some(Value::String(format)) if format == "json5" => options.format = InputFormat::Json5,
+        Some(_) => {
+            return Err(JsValue::from_str(
+                "Invalid options: format must be \"json\", \"jsonc\" or \"json5\"",
+            ))
+        }
+        None => {}
+    }
+    match obj.remove("caseInsensitive") {
+        Some(Value::Bool(case_insensitive)) => options.case_insensitive = case_insensitive,
+        Some(_) => {
+            return Err(JsValue::from_str(
+                "Invalid options: caseInsensitive must be a boolean",
+            ))
+        }
+        None => {}
+    }
+    Ok(options)
+}
+
 /// Validate a JDT transform specification without applying it.
 ///
 /// # Arguments
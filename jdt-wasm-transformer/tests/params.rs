@@ -0,0 +1,38 @@
+use jdt_wasm_transformer::{transform, transform_all, transform_text};
+use serde_json::{json, Value};
+
+fn parse(text: &str) -> Value {
+    serde_json::from_str(text).unwrap()
+}
+
+#[test]
+fn strings_stay_literal_without_params() {
+    let source = r#"{"Logs": "/var/log"}"#;
+    let spec = r#"{"Logs": "${HOME}/logs"}"#;
+    let expected = json!({"Logs": "${HOME}/logs"});
+    assert_eq!(parse(&transform(source, spec, None).unwrap()), expected);
+    let options = Some(r#"{"format": "jsonc"}"#.to_string());
+    assert_eq!(parse(&transform(source, spec, options).unwrap()), expected);
+    assert_eq!(
+        parse(&transform_all(source, &format!("[{spec}]"), None).unwrap()),
+        expected
+    );
+    assert_eq!(
+        transform_text(source, spec, None).unwrap(),
+        r#"{"Logs": "${HOME}/logs"}"#
+    );
+}
+
+#[test]
+fn references_resolve_when_params_are_given() {
+    let options = Some(r#"{"params": {"HOME": "/home/app"}}"#.to_string());
+    let out = transform(
+        r#"{}"#,
+        r#"{"Logs": "${HOME}/logs", "Raw": "$${HOME}"}"#,
+        options,
+    );
+    assert_eq!(
+        parse(&out.unwrap()),
+        json!({"Logs": "/home/app/logs", "Raw": "${HOME}"})
+    );
+}
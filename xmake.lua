@@ -119,6 +119,7 @@ target("test_all")
         -- Quality checks
         os.vrunv("cargo", {"fmt", "--all", "--", "--check"})
         os.vrunv("cargo", {"clippy", "--workspace", "--all-targets", "--", "-D", "warnings"})
+        os.vrunv("cargo", {"clippy", "--workspace", "--all-targets", "--all-features", "--", "-D", "warnings"})
 
         -- Run integration test suites
         local inputs = fetch_ms_suite(os, path)
@@ -129,6 +130,12 @@ target("test_all")
         
         -- Run all tests (unit + integration)
         os.vrunv("cargo", {"test", "--workspace"})
+
+        -- Again with the optional features: YAML, TOML and document key order
+        os.vrunv("cargo", {"test", "--workspace", "--all-features"})
+        os.vrunv("cargo", {"test", "--workspace", "--features", "preserve_order"})
+        os.vrunv("cargo", {"test", "-p", "jdt-codegen", "--features", "yaml", "--test", "yaml"})
+        os.vrunv("cargo", {"test", "-p", "jdt-codegen", "--features", "toml", "--test", "toml"})
         
         cprint("${green}OK:${clear} test_all")
     end)